@@ -0,0 +1,61 @@
+#![no_std]
+#![no_main]
+
+// smallest firmware using binlog!, built in ci so interned format strings
+// are linked the way map.ld lays them out
+
+use core::panic::{ PanicInfo };
+use core::time::{ Duration };
+use toolkit::cmd::{ Poll, Queue };
+use toolkit::runtime::{ RuntimeMain, Time };
+use toolkit::runtime::log::{ Level };
+use toolkit_unsafe::{ IPCByteBuf };
+use toolkit_unsafe::heap::{ Heap };
+
+use core::arch::global_asm;
+global_asm!(include_str!("trap.S"));
+
+#[global_allocator]
+static HEAP: Heap = Heap::empty();
+
+#[derive(Default)]
+struct Ticks {
+    now: Duration,
+}
+
+impl Time for Ticks {
+    fn time(&mut self) -> Duration {
+        self.now += Duration::from_micros(1);
+        self.now
+    }
+}
+
+struct NoQueue;
+
+impl Queue for NoQueue {
+    type Request = usize;
+    type Response = ();
+    type Error = ();
+
+    fn push(&mut self, _req: usize) -> Poll<Result<(), ()>> {
+        Poll::Ready(Err(()))
+    }
+
+    fn pop(&mut self) -> Poll<Result<(), ()>> {
+        Poll::Ready(Err(()))
+    }
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn main() {
+    let main: RuntimeMain<'_, Ticks, NoQueue, 1, 256, 1> = RuntimeMain::new(
+        Ticks::default(), NoQueue, |_| IPCByteBuf::default(),
+    );
+    let mut rt = main.as_ref();
+    toolkit::binlog!(rt, Level::Info, "irq {} status {:#06x}", 5u8, 0x1fu16);
+}
+
+#[panic_handler]
+fn panic(_info: &PanicInfo) -> ! {
+    loop { }
+}
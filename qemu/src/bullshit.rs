@@ -5,11 +5,31 @@ use core::panic::{ PanicInfo };
 use core::fmt::{ Write };
 use toolkit::runtime::{ RuntimeMain };
 use toolkit::cmd::{ Buf, Poll, Error };
+use toolkit::mem::frame::{ FrameAlloc };
 use toolkit_unsafe::{ IPCByteBuf };
+use toolkit_unsafe::heap::{ Heap };
 
 use core::arch::global_asm;
 global_asm!(include_str!("trap.S"));
 
+// laid out by map.ld
+unsafe extern "C" {
+    static _heap_start: u8;
+    static _heap_end: u8;
+    static _frames_start: u8;
+    static _frames_end: u8;
+}
+
+const FRAME_NR: usize = 64;
+
+fn region(start: *const u8, end: *const u8) -> IPCByteBuf<'static> {
+    let (start, end) = (start.addr(), end.addr());
+    unsafe { IPCByteBuf::new(start, end - start) }
+}
+
+#[global_allocator]
+static HEAP: Heap = Heap::empty();
+
 
 fn some_one<const L: usize>(mut logger: LogChan<L>) {
     writeln!(logger, "some_one some_one some_one some_one");
@@ -21,9 +41,10 @@ fn some_two<const L: usize>(mut logger: LogChan<L>) {
 
 #[unsafe(no_mangle)]
 pub extern "C" fn main() {
-    let bufdeque = Deque::<RawBuf, 8>::new(|idx| {
-        
-    });
+    HEAP.init(region(&raw const _heap_start, &raw const _heap_end));
+
+    let mut frames = FrameAlloc::<FRAME_NR>::new(region(&raw const _frames_start, &raw const _frames_end));
+    let bufdeque = Deque::<IPCByteBuf, 8>::full(|_| frames.alloc().unwrap_or_default());
     let runtime_inner = RuntimeInner::new(&logcell);
 
     some_one(logger);
@@ -1,6 +1,35 @@
+use core::array::{ from_fn };
+use core::hint::{ self };
+use core::time::{ Duration };
+use crate::register::{ RegWidth };
+use crate::runtime::{ Time };
+use crate::runtime::time::{ Deadline };
+use crate::collection::deque::{ Deque };
 use toolkit_unsafe::{ IPCByteBuf };
+use toolkit_unsafe::plain::{ FromBytes, AsBytes };
+use toolkit_unsafe::fence::{ self };
+
+pub mod stream;
+pub mod trace;
+
+#[cfg(test)]
+mod test;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Error {
+    EndOfBuf,
+    Timeout {
+        off: usize,
+    },
+}
 
 pub trait ByteBuf {
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
     fn rd8(&mut self, off: usize) -> u8;
     fn wr8(&mut self, off: usize, value: u8);
 
@@ -12,6 +41,20 @@ pub trait ByteBuf {
 
     fn rd64(&mut self, off: usize) -> u64;
     fn wr64(&mut self, off: usize, value: u64);
+
+    fn read_struct<T: FromBytes + AsBytes>(&mut self, off: usize) -> T {
+        let mut value = T::zeroed();
+        for (idx, byte) in value.as_bytes_mut().iter_mut().enumerate() {
+            *byte = self.rd8(off + idx);
+        }
+        value
+    }
+
+    fn write_struct<T: AsBytes>(&mut self, off: usize, value: &T) {
+        for (idx, byte) in value.as_bytes().iter().enumerate() {
+            self.wr8(off + idx, *byte);
+        }
+    }
 }
 
 pub trait VolatileByteBuf {
@@ -26,6 +69,93 @@ pub trait VolatileByteBuf {
 
     fn rd64_volatile(&mut self, off: usize) -> u64;
     fn wr64_volatile(&mut self, off: usize, value: u64);
+
+    fn rd8_volatile_ordered(&mut self, off: usize) -> u8 {
+        let value = self.rd8_volatile(off);
+        fence::io();
+        value
+    }
+
+    fn wr8_volatile_ordered(&mut self, off: usize, value: u8) {
+        fence::io();
+        self.wr8_volatile(off, value);
+    }
+
+    fn rd16_volatile_ordered(&mut self, off: usize) -> u16 {
+        let value = self.rd16_volatile(off);
+        fence::io();
+        value
+    }
+
+    fn wr16_volatile_ordered(&mut self, off: usize, value: u16) {
+        fence::io();
+        self.wr16_volatile(off, value);
+    }
+
+    fn rd32_volatile_ordered(&mut self, off: usize) -> u32 {
+        let value = self.rd32_volatile(off);
+        fence::io();
+        value
+    }
+
+    fn wr32_volatile_ordered(&mut self, off: usize, value: u32) {
+        fence::io();
+        self.wr32_volatile(off, value);
+    }
+
+    fn rd64_volatile_ordered(&mut self, off: usize) -> u64 {
+        let value = self.rd64_volatile(off);
+        fence::io();
+        value
+    }
+
+    fn wr64_volatile_ordered(&mut self, off: usize, value: u64) {
+        fence::io();
+        self.wr64_volatile(off, value);
+    }
+
+    fn modify<W: RegWidth, F: FnOnce(W) -> W>(&mut self, off: usize, f: F) {
+        let value = f(W::rd(self, off));
+        W::wr(self, off, value);
+    }
+
+    fn set_bits<W: RegWidth>(&mut self, off: usize, mask: W) {
+        self.modify(off, |reg: W| W::from_u64(reg.into_u64() | mask.into_u64()));
+    }
+
+    fn clear_bits<W: RegWidth>(&mut self, off: usize, mask: W) {
+        self.modify(off, |reg: W| W::from_u64(reg.into_u64() & !mask.into_u64()));
+    }
+
+    fn wait_until<W: RegWidth, T: Time>(
+        &mut self, time: &mut T, off: usize, mask: W, value: W, timeout: Duration,
+    ) -> Result<W, Error> {
+        let deadline = Deadline::new(time, timeout);
+        loop {
+            let reg = W::rd(self, off);
+            if reg.into_u64() & mask.into_u64() == value.into_u64() & mask.into_u64() {
+                return Ok(reg);
+            }
+            if deadline.is_expired(time) {
+                return Err(Error::Timeout { off });
+            }
+            hint::spin_loop();
+        }
+    }
+
+    fn read_struct_volatile<T: FromBytes + AsBytes>(&mut self, off: usize) -> T {
+        let mut value = T::zeroed();
+        for (idx, byte) in value.as_bytes_mut().iter_mut().enumerate() {
+            *byte = self.rd8_volatile(off + idx);
+        }
+        value
+    }
+
+    fn write_struct_volatile<T: AsBytes>(&mut self, off: usize, value: &T) {
+        for (idx, byte) in value.as_bytes().iter().enumerate() {
+            self.wr8_volatile(off + idx, *byte);
+        }
+    }
 }
 
 pub trait AtomicByteBuf {
@@ -46,7 +176,213 @@ pub struct MemByteBuf<'a> {
     mem: IPCByteBuf<'a>,
 }
 
+impl<'a> MemByteBuf<'a> {
+    pub fn new(mem: IPCByteBuf<'a>) -> Self {
+        Self {
+            mem,
+        }
+    }
+
+    pub fn into_inner(self) -> IPCByteBuf<'a> {
+        self.mem
+    }
+
+    pub fn addr(&self) -> usize {
+        self.mem.addr()
+    }
+
+    pub fn len(&self) -> usize {
+        self.mem.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.mem.is_empty()
+    }
+}
+
+impl<'a> From<IPCByteBuf<'a>> for MemByteBuf<'a> {
+    fn from(mem: IPCByteBuf<'a>) -> Self {
+        Self::new(mem)
+    }
+}
+
+impl<'a> From<MemByteBuf<'a>> for IPCByteBuf<'a> {
+    fn from(buf: MemByteBuf<'a>) -> Self {
+        buf.into_inner()
+    }
+}
+
+impl<B> ByteBuf for &mut B
+where B: ByteBuf + ?Sized {
+    fn len(&self) -> usize {
+        (**self).len()
+    }
+
+    fn rd8(&mut self, off: usize) -> u8 {
+        (**self).rd8(off)
+    }
+
+    fn wr8(&mut self, off: usize, value: u8) {
+        (**self).wr8(off, value);
+    }
+
+    fn rd16(&mut self, off: usize) -> u16 {
+        (**self).rd16(off)
+    }
+
+    fn wr16(&mut self, off: usize, value: u16) {
+        (**self).wr16(off, value);
+    }
+
+    fn rd32(&mut self, off: usize) -> u32 {
+        (**self).rd32(off)
+    }
+
+    fn wr32(&mut self, off: usize, value: u32) {
+        (**self).wr32(off, value);
+    }
+
+    fn rd64(&mut self, off: usize) -> u64 {
+        (**self).rd64(off)
+    }
+
+    fn wr64(&mut self, off: usize, value: u64) {
+        (**self).wr64(off, value);
+    }
+}
+
+impl<B> VolatileByteBuf for &mut B
+where B: VolatileByteBuf + ?Sized {
+    fn rd8_volatile(&mut self, off: usize) -> u8 {
+        (**self).rd8_volatile(off)
+    }
+
+    fn wr8_volatile(&mut self, off: usize, value: u8) {
+        (**self).wr8_volatile(off, value);
+    }
+
+    fn rd16_volatile(&mut self, off: usize) -> u16 {
+        (**self).rd16_volatile(off)
+    }
+
+    fn wr16_volatile(&mut self, off: usize, value: u16) {
+        (**self).wr16_volatile(off, value);
+    }
+
+    fn rd32_volatile(&mut self, off: usize) -> u32 {
+        (**self).rd32_volatile(off)
+    }
+
+    fn wr32_volatile(&mut self, off: usize, value: u32) {
+        (**self).wr32_volatile(off, value);
+    }
+
+    fn rd64_volatile(&mut self, off: usize) -> u64 {
+        (**self).rd64_volatile(off)
+    }
+
+    fn wr64_volatile(&mut self, off: usize, value: u64) {
+        (**self).wr64_volatile(off, value);
+    }
+}
+
+/*
+ * plain memory byte buffers
+ */
+macro_rules! plain_bytebuf {
+    ([$(const $nr:ident)?] $ty:ty, $len:expr) => {
+        impl$(<const $nr: usize>)? ByteBuf for $ty {
+            fn len(&self) -> usize {
+                $len(self)
+            }
+
+            fn rd8(&mut self, off: usize) -> u8 {
+                self[off]
+            }
+
+            fn wr8(&mut self, off: usize, value: u8) {
+                self[off] = value;
+            }
+
+            fn rd16(&mut self, off: usize) -> u16 {
+                u16::from_ne_bytes(from_fn(|idx| self[off + idx]))
+            }
+
+            fn wr16(&mut self, off: usize, value: u16) {
+                for (idx, byte) in value.to_ne_bytes().into_iter().enumerate() {
+                    self[off + idx] = byte;
+                }
+            }
+
+            fn rd32(&mut self, off: usize) -> u32 {
+                u32::from_ne_bytes(from_fn(|idx| self[off + idx]))
+            }
+
+            fn wr32(&mut self, off: usize, value: u32) {
+                for (idx, byte) in value.to_ne_bytes().into_iter().enumerate() {
+                    self[off + idx] = byte;
+                }
+            }
+
+            fn rd64(&mut self, off: usize) -> u64 {
+                u64::from_ne_bytes(from_fn(|idx| self[off + idx]))
+            }
+
+            fn wr64(&mut self, off: usize, value: u64) {
+                for (idx, byte) in value.to_ne_bytes().into_iter().enumerate() {
+                    self[off + idx] = byte;
+                }
+            }
+        }
+
+        impl$(<const $nr: usize>)? VolatileByteBuf for $ty {
+            fn rd8_volatile(&mut self, off: usize) -> u8 {
+                self.rd8(off)
+            }
+
+            fn wr8_volatile(&mut self, off: usize, value: u8) {
+                self.wr8(off, value);
+            }
+
+            fn rd16_volatile(&mut self, off: usize) -> u16 {
+                self.rd16(off)
+            }
+
+            fn wr16_volatile(&mut self, off: usize, value: u16) {
+                self.wr16(off, value);
+            }
+
+            fn rd32_volatile(&mut self, off: usize) -> u32 {
+                self.rd32(off)
+            }
+
+            fn wr32_volatile(&mut self, off: usize, value: u32) {
+                self.wr32(off, value);
+            }
+
+            fn rd64_volatile(&mut self, off: usize) -> u64 {
+                self.rd64(off)
+            }
+
+            fn wr64_volatile(&mut self, off: usize, value: u64) {
+                self.wr64(off, value);
+            }
+        }
+    };
+}
+
+plain_bytebuf!([] [u8], <[u8]>::len);
+plain_bytebuf!([const L] [u8; L], |_| L);
+plain_bytebuf!([const L] Deque<u8, L>, Deque::len);
+
+/*
+ * raw memory byte buffer
+ */
 impl ByteBuf for MemByteBuf<'_> {
+    fn len(&self) -> usize {
+        self.mem.len()
+    }
+
     fn rd8(&mut self, off: usize) -> u8 {
         self.mem.rd8(off)
     }
@@ -78,6 +414,14 @@ impl ByteBuf for MemByteBuf<'_> {
     fn wr64(&mut self, off: usize, value: u64) {
         self.mem.wr64(off, value);
     }
+
+    fn read_struct<T: FromBytes + AsBytes>(&mut self, off: usize) -> T {
+        self.mem.read_struct(off)
+    }
+
+    fn write_struct<T: AsBytes>(&mut self, off: usize, value: &T) {
+        self.mem.write_struct(off, value);
+    }
 }
 
 impl VolatileByteBuf for MemByteBuf<'_> {
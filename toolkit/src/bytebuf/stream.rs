@@ -0,0 +1,246 @@
+use core::fmt::{ self };
+use crate::bytebuf::{ ByteBuf, Error };
+
+/*
+ * sequential reader
+ */
+pub struct ByteReader<B> {
+    buf: B,
+    pos: usize,
+}
+
+impl<B> ByteReader<B>
+where B: ByteBuf {
+    pub fn new(buf: B) -> Self {
+        Self {
+            buf, pos: 0,
+        }
+    }
+
+    pub fn into_inner(self) -> B {
+        self.buf
+    }
+
+    pub fn get_ref(&self) -> &B {
+        &self.buf
+    }
+
+    pub fn pos(&self) -> usize {
+        self.pos
+    }
+
+    pub fn remaining(&self) -> usize {
+        self.buf.len().saturating_sub(self.pos)
+    }
+
+    pub fn seek(&mut self, pos: usize) -> Result<(), Error> {
+        if pos > self.buf.len() {
+            return Err(Error::EndOfBuf);
+        }
+        self.pos = pos;
+        Ok(())
+    }
+
+    fn advance(&mut self, len: usize) -> Result<usize, Error> {
+        if len > self.remaining() {
+            return Err(Error::EndOfBuf);
+        }
+        let off = self.pos;
+        self.pos += len;
+        Ok(off)
+    }
+
+    pub fn skip(&mut self, len: usize) -> Result<(), Error> {
+        self.advance(len).map(|_| ())
+    }
+
+    pub fn align_to(&mut self, align: usize) -> Result<(), Error> {
+        let Some(pos) = self.pos.checked_next_multiple_of(align) else {
+            return Err(Error::EndOfBuf);
+        };
+        self.skip(pos - self.pos)
+    }
+
+    pub fn read_bytes(&mut self, out: &mut [u8]) -> Result<(), Error> {
+        let off = self.advance(out.len())?;
+        for (idx, byte) in out.iter_mut().enumerate() {
+            *byte = self.buf.rd8(off + idx);
+        }
+        Ok(())
+    }
+
+    fn read_array<const N: usize>(&mut self) -> Result<[u8; N], Error> {
+        let mut bytes = [0; N];
+        self.read_bytes(&mut bytes)?;
+        Ok(bytes)
+    }
+
+    pub fn read_u8(&mut self) -> Result<u8, Error> {
+        let off = self.advance(1)?;
+        Ok(self.buf.rd8(off))
+    }
+
+    pub fn read_u16_be(&mut self) -> Result<u16, Error> {
+        self.read_array().map(u16::from_be_bytes)
+    }
+
+    pub fn read_u16_le(&mut self) -> Result<u16, Error> {
+        self.read_array().map(u16::from_le_bytes)
+    }
+
+    pub fn read_u32_be(&mut self) -> Result<u32, Error> {
+        self.read_array().map(u32::from_be_bytes)
+    }
+
+    pub fn read_u32_le(&mut self) -> Result<u32, Error> {
+        self.read_array().map(u32::from_le_bytes)
+    }
+
+    pub fn read_u64_be(&mut self) -> Result<u64, Error> {
+        self.read_array().map(u64::from_be_bytes)
+    }
+
+    pub fn read_u64_le(&mut self) -> Result<u64, Error> {
+        self.read_array().map(u64::from_le_bytes)
+    }
+
+    // unsigned LEB128, at most 10 bytes for a u64
+    pub fn read_varint(&mut self) -> Result<u64, Error> {
+        let mut value = 0;
+        for shift in (0..64).step_by(7) {
+            let byte = self.read_u8()?;
+            value |= u64::from(byte & 0x7F) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(Error::EndOfBuf)
+    }
+}
+
+/*
+ * sequential writer
+ */
+pub struct ByteWriter<B> {
+    buf: B,
+    pos: usize,
+}
+
+impl<B> ByteWriter<B>
+where B: ByteBuf {
+    pub fn new(buf: B) -> Self {
+        Self {
+            buf, pos: 0,
+        }
+    }
+
+    pub fn into_inner(self) -> B {
+        self.buf
+    }
+
+    pub fn get_ref(&self) -> &B {
+        &self.buf
+    }
+
+    pub fn pos(&self) -> usize {
+        self.pos
+    }
+
+    pub fn remaining(&self) -> usize {
+        self.buf.len().saturating_sub(self.pos)
+    }
+
+    pub fn seek(&mut self, pos: usize) -> Result<(), Error> {
+        if pos > self.buf.len() {
+            return Err(Error::EndOfBuf);
+        }
+        self.pos = pos;
+        Ok(())
+    }
+
+    fn advance(&mut self, len: usize) -> Result<usize, Error> {
+        if len > self.remaining() {
+            return Err(Error::EndOfBuf);
+        }
+        let off = self.pos;
+        self.pos += len;
+        Ok(off)
+    }
+
+    pub fn skip(&mut self, len: usize) -> Result<(), Error> {
+        self.advance(len).map(|_| ())
+    }
+
+    pub fn align_to(&mut self, align: usize) -> Result<(), Error> {
+        let Some(pos) = self.pos.checked_next_multiple_of(align) else {
+            return Err(Error::EndOfBuf);
+        };
+        let off = self.advance(pos - self.pos)?;
+        for idx in off..pos {
+            self.buf.wr8(idx, 0);
+        }
+        Ok(())
+    }
+
+    pub fn write_bytes(&mut self, bytes: &[u8]) -> Result<(), Error> {
+        let off = self.advance(bytes.len())?;
+        for (idx, byte) in bytes.iter().enumerate() {
+            self.buf.wr8(off + idx, *byte);
+        }
+        Ok(())
+    }
+
+    pub fn write_u8(&mut self, value: u8) -> Result<(), Error> {
+        let off = self.advance(1)?;
+        self.buf.wr8(off, value);
+        Ok(())
+    }
+
+    pub fn write_u16_be(&mut self, value: u16) -> Result<(), Error> {
+        self.write_bytes(&value.to_be_bytes())
+    }
+
+    pub fn write_u16_le(&mut self, value: u16) -> Result<(), Error> {
+        self.write_bytes(&value.to_le_bytes())
+    }
+
+    pub fn write_u32_be(&mut self, value: u32) -> Result<(), Error> {
+        self.write_bytes(&value.to_be_bytes())
+    }
+
+    pub fn write_u32_le(&mut self, value: u32) -> Result<(), Error> {
+        self.write_bytes(&value.to_le_bytes())
+    }
+
+    pub fn write_u64_be(&mut self, value: u64) -> Result<(), Error> {
+        self.write_bytes(&value.to_be_bytes())
+    }
+
+    pub fn write_u64_le(&mut self, value: u64) -> Result<(), Error> {
+        self.write_bytes(&value.to_le_bytes())
+    }
+
+    pub fn write_varint(&mut self, mut value: u64) -> Result<(), Error> {
+        let mut bytes = [0; 10];
+        let mut len = 0;
+        loop {
+            let byte = (value & 0x7F) as u8;
+            value >>= 7;
+            if value == 0 {
+                bytes[len] = byte;
+                len += 1;
+                break;
+            }
+            bytes[len] = byte | 0x80;
+            len += 1;
+        }
+        self.write_bytes(&bytes[..len])
+    }
+}
+
+impl<B> fmt::Write for ByteWriter<B>
+where B: ByteBuf {
+    fn write_str(&mut self, s: &str) -> Result<(), fmt::Error> {
+        self.write_bytes(s.as_bytes()).map_err(|_| fmt::Error)
+    }
+}
@@ -0,0 +1,227 @@
+use crate::bytebuf::{ ByteBuf, VolatileByteBuf, MemByteBuf, Error };
+use crate::bytebuf::stream::{ ByteReader, ByteWriter };
+use crate::bytebuf::trace::{ TraceByteBuf, TraceRing, TraceRecord, Access };
+use crate::collection::deque::{ Deque };
+use crate::runtime::{ Time };
+use core::time::{ Duration };
+use toolkit_unsafe::{ IPCByteBuf, plain };
+
+const BUFLEN: usize = 16;
+
+plain! {
+    #[derive(Debug, Default, PartialEq)]
+    struct TestHeader {
+        id: u32,
+        len: u16,
+        flags: u16,
+    }
+}
+
+#[derive(Default)]
+struct TestTime {
+    now: Duration,
+}
+
+impl Time for TestTime {
+    fn time(&mut self) -> Duration {
+        self.now += Duration::from_micros(1);
+        self.now
+    }
+}
+
+#[test]
+fn array_rd_wr() {
+    let mut buf = [0u8; BUFLEN];
+    assert_eq!(ByteBuf::len(&buf), BUFLEN);
+
+    buf.wr8(0, 0x11);
+    buf.wr16(2, 0x2233);
+    buf.wr32(4, 0x4455_6677);
+    buf.wr64(8, 0x8899_AABB_CCDD_EEFF);
+
+    assert_eq!(buf.rd8(0), 0x11);
+    assert_eq!(buf.rd16(2), 0x2233);
+    assert_eq!(buf.rd32(4), 0x4455_6677);
+    assert_eq!(buf.rd64(8), 0x8899_AABB_CCDD_EEFF);
+    assert_eq!(&buf[2..4], &0x2233u16.to_ne_bytes());
+
+    let slice = &mut buf[4..];
+    assert_eq!(slice.len(), BUFLEN - 4);
+    assert_eq!(slice.rd32_volatile(0), 0x4455_6677);
+    slice.wr32_volatile(0, 0x1234_5678);
+    assert_eq!(buf.rd32(4), 0x1234_5678);
+}
+
+#[test]
+fn deque_rd_wr() {
+    let mut deque = Deque::<u8, BUFLEN>::default();
+    for byte in 0..12 {
+        deque.push(byte);
+    }
+    for _ in 0..8 {
+        let _ = deque.pop();
+    }
+    for byte in 12..20 {
+        deque.push(byte);
+    }
+
+    //                           |>
+    // [ 16, 17, 18, 19, x, x, x, x, 8, 9, A, B, C, D, E, F ]
+    //                                 |>
+    assert_eq!(ByteBuf::len(&deque), 12);
+    assert_eq!(deque.rd8(0), 8);
+    assert_eq!(deque.rd8(11), 19);
+    assert_eq!(deque.rd32(6), u32::from_ne_bytes([14, 15, 16, 17]));
+
+    deque.wr16(7, 0xA5A5);
+    assert_eq!(deque.get(7), Some(&0xA5));
+    assert_eq!(deque.get(8), Some(&0xA5));
+    assert_eq!(deque.get(12), None);
+}
+
+#[test]
+fn struct_overlay() {
+    let mut buf = [0u8; BUFLEN];
+    let hdr = TestHeader {
+        id: 0x1BAD_C0DE, len: 42, flags: 0x8001,
+    };
+
+    buf.write_struct(4, &hdr);
+    assert_eq!(buf.rd32(4), 0x1BAD_C0DE);
+    assert_eq!(buf.rd16(8), 42);
+    assert_eq!(buf.read_struct::<TestHeader>(4), hdr);
+    assert_eq!(buf.read_struct_volatile::<TestHeader>(4), hdr);
+    assert_eq!([0u8; BUFLEN].read_struct::<TestHeader>(8), TestHeader::default());
+}
+
+#[test]
+fn stream_rd_wr() {
+    let mut buf = [0u8; BUFLEN];
+
+    let mut writer = ByteWriter::new(&mut buf);
+    assert_eq!(writer.write_u8(0xAB), Ok(()));
+    assert_eq!(writer.align_to(4), Ok(()));
+    assert_eq!(writer.write_u32_be(0x0102_0304), Ok(()));
+    assert_eq!(writer.write_u16_le(0x0506), Ok(()));
+    assert_eq!(writer.pos(), 10);
+    assert_eq!(writer.remaining(), 6);
+    assert_eq!(writer.write_u64_le(0), Err(Error::EndOfBuf));
+    assert_eq!(writer.write_bytes(b"tail!!"), Ok(()));
+    assert_eq!(writer.write_u8(0), Err(Error::EndOfBuf));
+
+    assert_eq!(&buf[..10], &[0xAB, 0, 0, 0, 1, 2, 3, 4, 6, 5]);
+
+    let mut reader = ByteReader::new(&mut buf);
+    assert_eq!(reader.read_u8(), Ok(0xAB));
+    assert_eq!(reader.align_to(4), Ok(()));
+    assert_eq!(reader.read_u32_be(), Ok(0x0102_0304));
+    assert_eq!(reader.read_u16_le(), Ok(0x0506));
+    assert_eq!(reader.read_u64_be(), Err(Error::EndOfBuf));
+    assert_eq!(reader.pos(), 10);
+
+    let mut tail = [0u8; 6];
+    assert_eq!(reader.read_bytes(&mut tail), Ok(()));
+    assert_eq!(&tail, b"tail!!");
+    assert_eq!(reader.remaining(), 0);
+    assert_eq!(reader.skip(1), Err(Error::EndOfBuf));
+}
+
+#[test]
+fn mem_rd_wr() {
+    let mut raw = [0u8; BUFLEN];
+    let addr = raw.as_ptr().addr();
+
+    let mut buf = MemByteBuf::from(IPCByteBuf::from(&mut raw[..]));
+    assert_eq!(buf.addr(), addr);
+    assert_eq!(buf.len(), BUFLEN);
+
+    buf.wr32(0, 0x1BAD_C0DE);
+    buf.write_struct(8, &TestHeader { id: 1, len: 2, flags: 3 });
+    assert_eq!(buf.rd32_volatile(0), 0x1BAD_C0DE);
+    assert_eq!(buf.read_struct::<TestHeader>(8).flags, 3);
+
+    let mem: IPCByteBuf = buf.into();
+    assert_eq!(mem.rd32(0), 0x1BAD_C0DE);
+    assert_eq!(raw[8], 1);
+}
+
+#[test]
+fn ordered_rd_wr() {
+    let mut raw = [0u8; BUFLEN];
+    let mut buf = MemByteBuf::from(IPCByteBuf::from(&mut raw[..]));
+
+    buf.wr8_volatile_ordered(0, 0x11);
+    buf.wr16_volatile_ordered(2, 0x2233);
+    buf.wr32_volatile_ordered(4, 0x4455_6677);
+    buf.wr64_volatile_ordered(8, 0x8899_AABB_CCDD_EEFF);
+
+    assert_eq!(buf.rd8_volatile_ordered(0), 0x11);
+    assert_eq!(buf.rd16_volatile_ordered(2), 0x2233);
+    assert_eq!(buf.rd32_volatile_ordered(4), 0x4455_6677);
+    assert_eq!(buf.rd64_volatile_ordered(8), 0x8899_AABB_CCDD_EEFF);
+
+    // the provided ordered accessors go through the plain volatile ones
+    let mut arr = [0u8; BUFLEN];
+    arr.wr32_volatile_ordered(4, 0x1234_5678);
+    assert_eq!(arr.rd32(4), 0x1234_5678);
+    assert_eq!(arr.rd32_volatile_ordered(4), 0x1234_5678);
+}
+
+#[test]
+fn trace_ring() {
+    let mut io = [0u8; BUFLEN];
+    let mut trace = TraceByteBuf::new(&mut io, TestTime::default(), TraceRing::<2>::default());
+
+    trace.wr32_volatile(4, 0xC0DE);
+    assert_eq!(trace.rd16_volatile(4), 0xC0DE);
+    trace.set_enabled(false);
+    trace.wr8_volatile(0, 1);
+    trace.set_enabled(true);
+    assert_eq!(trace.rd8_volatile(0), 1);
+
+    let mut records = trace.sink().iter();
+    assert_eq!(records.next(), Some(&TraceRecord {
+        time: Duration::from_micros(2), access: Access::Read, width: 2, off: 4, value: 0xC0DE,
+    }));
+    assert_eq!(records.next(), Some(&TraceRecord {
+        time: Duration::from_micros(3), access: Access::Read, width: 1, off: 0, value: 1,
+    }));
+    assert_eq!(records.next(), None);
+    assert_eq!(io[0], 1);
+}
+
+#[test]
+fn bits_wait() {
+    let mut io = [0u8; BUFLEN];
+    let mut time = TestTime::default();
+
+    io.set_bits(4, 0x8001u32);
+    io.set_bits(4, 0x0010u32);
+    io.clear_bits(4, 0x0001u32);
+    assert_eq!(io.rd32(4), 0x8010);
+    io.modify(8, |reg: u16| reg + 7);
+    assert_eq!(io.rd16(8), 7);
+
+    let timeout = Duration::from_micros(10);
+    assert_eq!(io.wait_until(&mut time, 4, 0x8000u32, 0x8000, timeout), Ok(0x8010));
+    assert_eq!(io.wait_until(&mut time, 8, 0x1u16, 0x0, timeout), Err(Error::Timeout { off: 8 }));
+    assert!(time.now >= timeout);
+}
+
+#[test]
+fn varint_rd_wr() {
+    let mut writer = ByteWriter::new([0u8; 16]);
+    assert_eq!(writer.write_varint(0x7F), Ok(()));
+    assert_eq!(writer.write_varint(300), Ok(()));
+    assert_eq!(writer.write_varint(u64::MAX), Ok(()));
+    assert_eq!(writer.pos(), 13);
+    assert_eq!(&writer.get_ref()[..3], &[0x7F, 0xAC, 0x02]);
+    assert_eq!(writer.write_varint(u64::MAX), Err(Error::EndOfBuf));
+    assert_eq!(writer.pos(), 13);
+
+    let mut reader = ByteReader::new(writer.into_inner());
+    assert_eq!(reader.read_varint(), Ok(0x7F));
+    assert_eq!(reader.read_varint(), Ok(300));
+    assert_eq!(reader.read_varint(), Ok(u64::MAX));
+    assert_eq!(reader.read_varint(), Ok(0));
+}
@@ -0,0 +1,171 @@
+use core::time::{ Duration };
+use crate::bytebuf::{ VolatileByteBuf };
+use crate::collection::deque::{ Deque, DequeRefIter };
+use crate::runtime::{ Runtime, Time };
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Access {
+    #[default]
+    Read,
+    Write,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct TraceRecord {
+    pub time: Duration,
+    pub access: Access,
+    pub width: u8,
+    pub off: usize,
+    pub value: u64,
+}
+
+pub trait TraceSink {
+    fn record(&mut self, rec: &TraceRecord);
+}
+
+/*
+ * fixed trace ring
+ */
+#[derive(Default)]
+pub struct TraceRing<const L: usize> {
+    ring: Deque<TraceRecord, L>,
+}
+
+impl<const L: usize>
+TraceRing<L> {
+    pub fn len(&self) -> usize {
+        self.ring.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ring.is_empty()
+    }
+
+    pub fn iter(&self) -> DequeRefIter<'_, TraceRecord> {
+        self.ring.iter()
+    }
+
+    pub fn pop(&mut self) -> Option<TraceRecord> {
+        self.ring.pop()
+    }
+}
+
+impl<const L: usize>
+TraceSink for TraceRing<L> {
+    fn record(&mut self, rec: &TraceRecord) {
+        if self.ring.is_full() {
+            self.ring.pop();
+        }
+        self.ring.push(*rec);
+    }
+}
+
+impl<R> TraceSink for R
+where R: Runtime {
+    fn record(&mut self, rec: &TraceRecord) {
+        let access = match rec.access {
+            Access::Read => "rd",
+            Access::Write => "wr",
+        };
+        crate::trace!(self, "{:?} mmio {}{} {:#x} {:#x}",
+            rec.time, access, rec.width * 8, rec.off, rec.value);
+    }
+}
+
+/*
+ * tracing wrapper
+ */
+pub struct TraceByteBuf<B, T, S> {
+    io: B,
+    time: T,
+    sink: S,
+    enabled: bool,
+}
+
+impl<B, T, S> TraceByteBuf<B, T, S>
+where B: VolatileByteBuf, T: Time, S: TraceSink {
+    pub fn new(io: B, time: T, sink: S) -> Self {
+        Self {
+            io, time, sink,
+            enabled: true,
+        }
+    }
+
+    pub fn into_inner(self) -> B {
+        self.io
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+    pub fn sink(&self) -> &S {
+        &self.sink
+    }
+
+    pub fn sink_mut(&mut self) -> &mut S {
+        &mut self.sink
+    }
+
+    fn trace(&mut self, access: Access, width: u8, off: usize, value: u64) {
+        if !self.enabled {
+            return;
+        }
+        let rec = TraceRecord {
+            time: self.time.time(),
+            access, width, off, value,
+        };
+        self.sink.record(&rec);
+    }
+}
+
+impl<B, T, S> VolatileByteBuf for TraceByteBuf<B, T, S>
+where B: VolatileByteBuf, T: Time, S: TraceSink {
+    fn rd8_volatile(&mut self, off: usize) -> u8 {
+        let value = self.io.rd8_volatile(off);
+        self.trace(Access::Read, 1, off, value.into());
+        value
+    }
+
+    fn wr8_volatile(&mut self, off: usize, value: u8) {
+        self.trace(Access::Write, 1, off, value.into());
+        self.io.wr8_volatile(off, value);
+    }
+
+    fn rd16_volatile(&mut self, off: usize) -> u16 {
+        let value = self.io.rd16_volatile(off);
+        self.trace(Access::Read, 2, off, value.into());
+        value
+    }
+
+    fn wr16_volatile(&mut self, off: usize, value: u16) {
+        self.trace(Access::Write, 2, off, value.into());
+        self.io.wr16_volatile(off, value);
+    }
+
+    fn rd32_volatile(&mut self, off: usize) -> u32 {
+        let value = self.io.rd32_volatile(off);
+        self.trace(Access::Read, 4, off, value.into());
+        value
+    }
+
+    fn wr32_volatile(&mut self, off: usize, value: u32) {
+        self.trace(Access::Write, 4, off, value.into());
+        self.io.wr32_volatile(off, value);
+    }
+
+    fn rd64_volatile(&mut self, off: usize) -> u64 {
+        let value = self.io.rd64_volatile(off);
+        self.trace(Access::Read, 8, off, value);
+        value
+    }
+
+    fn wr64_volatile(&mut self, off: usize, value: u64) {
+        self.trace(Access::Write, 8, off, value);
+        self.io.wr64_volatile(off, value);
+    }
+}
@@ -0,0 +1,159 @@
+use core::ops::{ Range };
+use crate::bytebuf::{ ByteBuf };
+use toolkit_unsafe::{ IPCByteBuf };
+
+#[cfg(test)]
+mod test;
+
+pub const CRC32_POLY: u32 = 0xEDB8_8320;
+pub const CRC32C_POLY: u32 = 0x82F6_3B78;
+
+const fn crc_table(poly: u32) -> [u32; 256] {
+    let mut table = [0; 256];
+    let mut idx = 0;
+    while idx < 256 {
+        let mut crc = idx as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = match crc & 1 {
+                0 => crc >> 1,
+                _ => (crc >> 1) ^ poly,
+            };
+            bit += 1;
+        }
+        table[idx] = crc;
+        idx += 1;
+    }
+    table
+}
+
+/*
+ * table driven crc
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Crc<const POLY: u32> {
+    crc: u32,
+}
+
+pub type Crc32 = Crc<CRC32_POLY>;
+pub type Crc32c = Crc<CRC32C_POLY>;
+
+impl<const POLY: u32>
+Default for Crc<POLY> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const POLY: u32>
+Crc<POLY> {
+    const TABLE: [u32; 256] = crc_table(POLY);
+
+    pub fn new() -> Self {
+        Self {
+            crc: !0,
+        }
+    }
+
+    pub fn checksum(data: &[u8]) -> u32 {
+        let mut crc = Self::new();
+        crc.update(data);
+        crc.finish()
+    }
+
+    fn update_byte(&mut self, byte: u8) {
+        let idx = (self.crc ^ u32::from(byte)) & 0xFF;
+        self.crc = (self.crc >> 8) ^ Self::TABLE[idx as usize];
+    }
+
+    pub fn update(&mut self, data: &[u8]) {
+        for byte in data {
+            self.update_byte(*byte);
+        }
+    }
+
+    pub fn update_buf<B: ByteBuf + ?Sized>(&mut self, buf: &mut B, range: Range<usize>) {
+        for off in range {
+            self.update_byte(buf.rd8(off));
+        }
+    }
+
+    pub fn update_ipcbuf(&mut self, buf: &IPCByteBuf, range: Range<usize>) {
+        for off in range {
+            self.update_byte(buf.rd8(off));
+        }
+    }
+
+    pub fn finish(&self) -> u32 {
+        !self.crc
+    }
+}
+
+/*
+ * internet ones' complement checksum
+ */
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct InetChecksum {
+    sum: u64,
+    odd: Option<u8>,
+}
+
+fn fold(mut sum: u64) -> u16 {
+    while sum > 0xFFFF {
+        sum = (sum & 0xFFFF) + (sum >> 16);
+    }
+    sum as u16
+}
+
+impl InetChecksum {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn checksum(data: &[u8]) -> u16 {
+        let mut sum = Self::new();
+        sum.update(data);
+        sum.finish()
+    }
+
+    pub fn incremental(checksum: u16, old: u16, new: u16) -> u16 {
+        let sum = u64::from(!checksum) + u64::from(!old) + u64::from(new);
+        !fold(sum)
+    }
+
+    fn update_byte(&mut self, byte: u8) {
+        match self.odd.take() {
+            None => self.odd = Some(byte),
+            Some(hi) => self.sum += u64::from(u16::from_be_bytes([hi, byte])),
+        }
+    }
+
+    pub fn add_u16(&mut self, value: u16) {
+        for byte in value.to_be_bytes() {
+            self.update_byte(byte);
+        }
+    }
+
+    pub fn update(&mut self, data: &[u8]) {
+        for byte in data {
+            self.update_byte(*byte);
+        }
+    }
+
+    pub fn update_buf<B: ByteBuf + ?Sized>(&mut self, buf: &mut B, range: Range<usize>) {
+        for off in range {
+            self.update_byte(buf.rd8(off));
+        }
+    }
+
+    pub fn update_ipcbuf(&mut self, buf: &IPCByteBuf, range: Range<usize>) {
+        for off in range {
+            self.update_byte(buf.rd8(off));
+        }
+    }
+
+    pub fn finish(&self) -> u16 {
+        let pad = self.odd.map_or(0, |hi| u64::from(u16::from_be_bytes([hi, 0])));
+        !fold(self.sum + pad)
+    }
+}
@@ -0,0 +1,60 @@
+use crate::checksum::{ Crc32, Crc32c, InetChecksum };
+use toolkit_unsafe::{ IPCByteBuf };
+
+const CHECK: &[u8] = b"123456789";
+
+// 20 byte IPv4 header with its checksum field zeroed
+const IPHDR: [u8; 20] = [
+    0x45, 0x00, 0x00, 0x73, 0x00, 0x00, 0x40, 0x00, 0x40, 0x11,
+    0x00, 0x00, 0xC0, 0xA8, 0x00, 0x01, 0xC0, 0xA8, 0x00, 0xC7,
+];
+
+#[test]
+fn crc32_check() {
+    assert_eq!(Crc32::checksum(CHECK), 0xCBF4_3926);
+    assert_eq!(Crc32c::checksum(CHECK), 0xE306_9283);
+    assert_eq!(Crc32::checksum(&[]), 0);
+
+    let mut crc = Crc32c::new();
+    crc.update(&CHECK[..4]);
+    crc.update(&CHECK[4..]);
+    assert_eq!(crc.finish(), 0xE306_9283);
+
+    let mut raw = [0u8; 16];
+    raw[3..12].copy_from_slice(CHECK);
+
+    let mut crc = Crc32::new();
+    crc.update_buf(&mut raw, 3..12);
+    assert_eq!(crc.finish(), 0xCBF4_3926);
+
+    let buf = IPCByteBuf::from(&mut raw[..]);
+    let mut crc = Crc32c::new();
+    crc.update_ipcbuf(&buf, 3..12);
+    assert_eq!(crc.finish(), 0xE306_9283);
+}
+
+#[test]
+fn inet_check() {
+    assert_eq!(InetChecksum::checksum(&IPHDR), 0xB861);
+
+    let mut sum = InetChecksum::new();
+    sum.update(&IPHDR[..5]);
+    sum.update(&IPHDR[5..]);
+    assert_eq!(sum.finish(), 0xB861);
+
+    let mut hdr = IPHDR;
+    hdr[10..12].copy_from_slice(&0xB861u16.to_be_bytes());
+    assert_eq!(InetChecksum::checksum(&hdr), 0);
+
+    // decrement ttl in place
+    hdr[8] = 0x3F;
+    let checksum = InetChecksum::incremental(0xB861, 0x4011, 0x3F11);
+    hdr[10..12].copy_from_slice(&[0, 0]);
+    assert_eq!(InetChecksum::checksum(&hdr), checksum);
+
+    let mut sum = InetChecksum::new();
+    sum.update(&[0xAB]);
+    assert_eq!(sum.finish(), !0xAB00);
+    sum.add_u16(0x0102);
+    assert_eq!(sum.finish(), !0xAD01);
+}
@@ -1,4 +1,5 @@
 pub mod rw;
+pub mod future;
 
 pub enum Poll<T> {
     Ready(T),
@@ -0,0 +1,100 @@
+use core::future::{ Future };
+use core::pin::{ Pin };
+use core::task::{ self, Context };
+use crate::cmd::{ Poll, Queue };
+
+impl<T> From<Poll<T>> for task::Poll<T> {
+    fn from(poll: Poll<T>) -> Self {
+        match poll {
+            Poll::Ready(value) => task::Poll::Ready(value),
+            Poll::Pending => task::Poll::Pending,
+        }
+    }
+}
+
+// queues and timers have no completion event to hang a waker on, a pending
+// operation registers nothing and is polled again once the executor idled
+pub(crate) fn pending<T>() -> task::Poll<T> {
+    task::Poll::Pending
+}
+
+pub struct Push<'a, Q>
+where Q: Queue + ?Sized {
+    queue: &'a mut Q,
+    req: Q::Request,
+}
+
+// a pending push keeps nothing, every attempt hands the queue its own copy
+impl<'a, Q> Future for Push<'a, Q>
+where Q: Queue + ?Sized, Q::Request: Clone + Unpin {
+    type Output = Result<(), Q::Error>;
+
+    fn poll(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> task::Poll<Self::Output> {
+        let this = self.get_mut();
+        match this.queue.push(this.req.clone()) {
+            Poll::Ready(res) => task::Poll::Ready(res),
+            Poll::Pending => pending(),
+        }
+    }
+}
+
+pub struct Pop<'a, Q>
+where Q: Queue + ?Sized {
+    queue: &'a mut Q,
+}
+
+impl<'a, Q> Future for Pop<'a, Q>
+where Q: Queue + ?Sized {
+    type Output = Result<Q::Response, Q::Error>;
+
+    fn poll(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> task::Poll<Self::Output> {
+        match self.get_mut().queue.pop() {
+            Poll::Ready(res) => task::Poll::Ready(res),
+            Poll::Pending => pending(),
+        }
+    }
+}
+
+pub trait QueueExt: Queue {
+    fn push_async(&mut self, req: Self::Request) -> Push<'_, Self> {
+        Push {
+            queue: self, req,
+        }
+    }
+
+    fn pop_async(&mut self) -> Pop<'_, Self> {
+        Pop {
+            queue: self,
+        }
+    }
+}
+
+impl<Q> QueueExt for Q
+where Q: Queue + ?Sized { }
+
+/*
+ * give the other tasks a turn
+ */
+#[derive(Default)]
+pub struct YieldNow {
+    yielded: bool,
+}
+
+impl Future for YieldNow {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> task::Poll<()> {
+        let this = self.get_mut();
+        if this.yielded {
+            return task::Poll::Ready(());
+        }
+        // the only pending future that is ready again right away
+        this.yielded = true;
+        cx.waker().wake_by_ref();
+        task::Poll::Pending
+    }
+}
+
+pub fn yield_now() -> YieldNow {
+    YieldNow::default()
+}
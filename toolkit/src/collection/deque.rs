@@ -1,5 +1,5 @@
 use core::array::{ from_fn };
-use core::ops::{ Range };
+use core::ops::{ Range, Index, IndexMut };
 use core::slice::{ Iter, IterMut };
 use core::iter::{ FusedIterator };
 use crate::collection::cursor::{ Cursor };
@@ -75,6 +75,14 @@ Deque<I, LEN> {
         }
     }
 
+    // every slot built by `ctr` is an item
+    pub fn full<Ctr: FnMut(usize) -> I>(ctr: Ctr) -> Self {
+        Self {
+            full: true,
+            ..Self::new(ctr)
+        }
+    }
+
     pub fn is_stack(&self) -> bool {
         self.stack
     }
@@ -120,17 +128,14 @@ Deque<I, LEN> {
     }
 
     fn as_slices(&self) -> (&[I], &[I]) {
-        let (left, right) = self.slice_ranges();
-        let (left_left, left_right) = self.buf.split_at(left.end);
-        let (_, right_right) = left_right.split_at(right.start);
-        (left_left, right_right)
+        let (first, second) = self.slice_ranges();
+        (&self.buf[first], &self.buf[second])
     }
 
     fn as_mut_slices(&mut self) -> (&mut [I], &mut [I]) {
-        let (left, right) = self.slice_ranges();
-        let (left_left, left_right) = self.buf.split_at_mut(left.end);
-        let (_, right_right) = left_right.split_at_mut(right.start);
-        (left_left, right_right)
+        let (first, second) = self.slice_ranges();
+        let (lower, upper) = self.buf.split_at_mut(first.start);
+        (&mut upper[..first.len()], &mut lower[second])
     }
 
     pub fn is_full(&self) -> bool {
@@ -145,6 +150,24 @@ Deque<I, LEN> {
         self.len() == 0
     }
 
+    fn pos(&self, idx: usize) -> Option<usize> {
+        let (first, second) = self.slice_ranges();
+        match idx.checked_sub(first.len()) {
+            None => Some(first.start + idx),
+            Some(idx) if idx < second.len() => Some(second.start + idx),
+            Some(_) => None,
+        }
+    }
+
+    pub fn get(&self, idx: usize) -> Option<&I> {
+        self.buf.get(self.pos(idx)?)
+    }
+
+    pub fn get_mut(&mut self, idx: usize) -> Option<&mut I> {
+        let pos = self.pos(idx)?;
+        self.buf.get_mut(pos)
+    }
+
     pub fn iter(&self) -> DequeRefIter<'_, I> {
         let (first, second) = self.as_slices();
         DequeRefIter {
@@ -187,6 +210,30 @@ where I: Copy {
     }
 }
 
+impl<I, const L: usize>
+Index<usize> for Deque<I, L> {
+    type Output = I;
+
+    fn index(&self, idx: usize) -> &I {
+        let (first, second) = self.slice_ranges();
+        match idx.checked_sub(first.len()) {
+            None => &self.buf[first][idx],
+            Some(idx) => &self.buf[second][idx],
+        }
+    }
+}
+
+impl<I, const L: usize>
+IndexMut<usize> for Deque<I, L> {
+    fn index_mut(&mut self, idx: usize) -> &mut I {
+        let (first, second) = self.slice_ranges();
+        match idx.checked_sub(first.len()) {
+            None => &mut self.buf[first][idx],
+            Some(idx) => &mut self.buf[second][idx],
+        }
+    }
+}
+
 impl<'a, I, const L: usize>
 IntoIterator for &'a Deque<I, L> {
     type Item = &'a I;
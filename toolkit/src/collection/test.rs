@@ -311,3 +311,50 @@ fn queue_equals() {
     assert_eq!(ldeque, rdeque);
     assert_eq!(rdeque, ldeque);
 }
+
+#[test]
+fn queue_iter() {
+    // [ 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, A, B, C ]
+    let buf: [TestItem; ITEMNR] = core::array::from_fn(|i|
+        TestItem::new(i.try_into().unwrap())
+    );
+
+    let mut deque = Deque::<TestItem, ITEMNR>::default();
+    assert_eq!(deque.iter().next(), None);
+
+    for i in 0..10 {
+        deque.push(buf[i]);
+    }
+    for _ in 0..4 {
+        let _ = deque.pop();
+    }
+    //                                 |>
+    // [ x, x, x, x, 4, 5, 6, 7, 8, 9, x, x, x ]
+    //               |>
+    assert!(deque.iter().eq(buf[4..10].iter()));
+
+    for i in 10..ITEMNR {
+        deque.push(buf[i]);
+    }
+    for i in 0..4 {
+        deque.push(buf[i]);
+    }
+    //               |>
+    // [ 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, A, B, C ]
+    //               |>
+    assert!(deque.iter().eq(buf[4..].iter().chain(buf[..4].iter())));
+
+    for item in deque.iter_mut() {
+        item.data = 0;
+    }
+    assert_eq!(deque.get(0), Some(&TestItem { id: buf[4].id, data: 0 }));
+    assert_eq!(deque[ITEMNR - 1].id, buf[3].id);
+
+    let empty = Deque::<u8, ITEMNR>::new(|i| i as u8);
+    assert!(empty.is_empty());
+    assert_eq!(empty.iter().next(), None);
+
+    let full = Deque::<u8, ITEMNR>::full(|i| i as u8);
+    assert!(full.is_full());
+    assert!(full.iter().copied().eq(0..ITEMNR as u8));
+}
@@ -1,4 +1,9 @@
 use core::fmt;
+use toolkit_unsafe::{ plain };
+use toolkit_unsafe::plain::{ FromBytes };
+
+#[cfg(test)]
+mod test;
 
 const ELF_MAGICK: u32 = 0x7F454C46;
 const ELF_FORMAT_32BIT: u8 = 1;
@@ -7,6 +12,7 @@ const ELF_FORMAT_64BIT: u8 = 2;
 #[derive(Debug)]
 pub enum Error {
     Fatal,
+    NotFound,
 }
 
 #[derive(Debug, Default)]
@@ -17,10 +23,9 @@ pub enum ElfFormat {
     Bit64,
 }
 
-const ELF_FORMAT_OFFSET: usize = 4;
-const ELF_ENDIAN_OFFSET: usize = 5;
 const ELF_ENDIAN_LITTLE: u8 = 1;
 const ELF_ENDIAN_BIG: u8 = 2;
+const ELF_VERSION_CURRENT: u32 = 1;
 
 #[derive(Default)]
 enum ElfEndian {
@@ -30,6 +35,29 @@ enum ElfEndian {
     Big,
 }
 
+impl ElfEndian {
+    fn u16(&self, value: u16) -> u16 {
+        match self {
+            ElfEndian::Big => u16::from_be(value),
+            _ => u16::from_le(value),
+        }
+    }
+
+    fn u32(&self, value: u32) -> u32 {
+        match self {
+            ElfEndian::Big => u32::from_be(value),
+            _ => u32::from_le(value),
+        }
+    }
+
+    fn u64(&self, value: u64) -> u64 {
+        match self {
+            ElfEndian::Big => u64::from_be(value),
+            _ => u64::from_le(value),
+        }
+    }
+}
+
 #[derive(Default)]
 enum ElfFileType {
     #[default]
@@ -41,6 +69,69 @@ enum ElfFileType {
     Specific,
 }
 
+impl From<u16> for ElfFileType {
+    fn from(value: u16) -> Self {
+        match value {
+            1 => ElfFileType::Relocatable,
+            2 => ElfFileType::Executable,
+            3 => ElfFileType::Shared,
+            4 => ElfFileType::Core,
+            0xFE00.. => ElfFileType::Specific,
+            _ => ElfFileType::Unknown,
+        }
+    }
+}
+
+plain! {
+    struct RawElfId {
+        magick: [u8; 4],
+        format: u8,
+        endian: u8,
+        version: u8,
+        osabi: u8,
+        abi_version: u8,
+        pad: [u8; 7],
+    }
+}
+
+plain! {
+    struct RawElf32Header {
+        id: RawElfId,
+        ftype: u16,
+        machine: u16,
+        version: u32,
+        entry: u32,
+        segmenttbl: u32,
+        sectiontbl: u32,
+        flags: u32,
+        hdrlen: u16,
+        segmentlen: u16,
+        segmentnr: u16,
+        sectionlen: u16,
+        sectionnr: u16,
+        sectionnametbl: u16,
+    }
+}
+
+plain! {
+    struct RawElf64Header {
+        id: RawElfId,
+        ftype: u16,
+        machine: u16,
+        version: u32,
+        entry: u64,
+        segmenttbl: u64,
+        sectiontbl: u64,
+        flags: u32,
+        hdrlen: u16,
+        segmentlen: u16,
+        segmentnr: u16,
+        sectionlen: u16,
+        sectionnr: u16,
+        sectionnametbl: u16,
+    }
+}
+
 #[derive(Default)]
 pub struct ElfId {
     pub magick: u32,
@@ -121,6 +212,63 @@ enum ElfSectionType {
     HighCpu,
 }
 
+impl From<u32> for ElfSectionType {
+    fn from(value: u32) -> Self {
+        match value {
+            0 => ElfSectionType::Null,
+            1 => ElfSectionType::Progbits,
+            2 => ElfSectionType::Symbol,
+            3 => ElfSectionType::Str,
+            4 => ElfSectionType::RelocExpl,
+            5 => ElfSectionType::Hash,
+            6 => ElfSectionType::Dynamic,
+            7 => ElfSectionType::Note,
+            8 => ElfSectionType::NoBits,
+            9 => ElfSectionType::RelocImpl,
+            11 => ElfSectionType::DynSymbol,
+            14 => ElfSectionType::Init,
+            15 => ElfSectionType::Fini,
+            16 => ElfSectionType::PreInit,
+            17 => ElfSectionType::Group,
+            0x6000_0000 => ElfSectionType::LowOS,
+            0x6000_0001..0x7000_0000 => ElfSectionType::HighOS,
+            0x7000_0000 => ElfSectionType::LowCpu,
+            0x7000_0001..0x8000_0000 => ElfSectionType::HighCpu,
+            _ => ElfSectionType::Unspecified,
+        }
+    }
+}
+
+plain! {
+    struct RawElf32Section {
+        nameidx: u32,
+        stype: u32,
+        flags: u32,
+        addr: u32,
+        offset: u32,
+        len: u32,
+        link: u32,
+        info: u32,
+        align: u32,
+        entrylen: u32,
+    }
+}
+
+plain! {
+    struct RawElf64Section {
+        nameidx: u32,
+        stype: u32,
+        flags: u64,
+        addr: u64,
+        offset: u64,
+        len: u64,
+        link: u32,
+        info: u32,
+        align: u64,
+        entrylen: u64,
+    }
+}
+
 #[derive(Default, Copy, Clone)]
 struct Elf32Section {
     nameidx: u32,
@@ -161,6 +309,29 @@ impl Default for ElfSection {
     }
 }
 
+impl ElfSection {
+    fn nameidx(&self) -> usize {
+        match self {
+            ElfSection::Bit32(sec) => sec.nameidx as usize,
+            ElfSection::Bit64(sec) => sec.nameidx as usize,
+        }
+    }
+
+    fn data<'d>(&self, data: &'d [u8]) -> Result<&'d [u8], Error> {
+        let (stype, offset, len) = match self {
+            ElfSection::Bit32(sec) => (sec.stype, u64::from(sec.offset), sec.len),
+            ElfSection::Bit64(sec) => (sec.stype, sec.offset, sec.len),
+        };
+        // occupies no space in the file
+        if let ElfSectionType::NoBits = stype {
+            return Ok(&[]);
+        }
+        let offset = usize::try_from(offset).map_err(|_| Error::Fatal)?;
+        let len = usize::try_from(len).map_err(|_| Error::Fatal)?;
+        data.get(offset..offset.checked_add(len).ok_or(Error::Fatal)?).ok_or(Error::Fatal)
+    }
+}
+
 #[derive(Default, Copy, Clone)]
 enum ElfSegmentType {
     #[default]
@@ -249,23 +420,153 @@ where L: fmt::Write {
     pub fn pull(&mut self, data: &[u8]) -> Result<(), Error> {
         writeln!(self.logger, "trying to pull data from stream");
 
-        self.id.magick = u32::from_be_bytes(data[0..4].try_into().unwrap());
+        let Some(id) = RawElfId::read_from(data) else {
+            return Err(Error::Fatal);
+        };
+
+        self.id.magick = u32::from_be_bytes(id.magick);
+        if self.id.magick != ELF_MAGICK {
+            return Err(Error::Fatal);
+        }
 
-        self.id.format = match data[ELF_FORMAT_OFFSET] {
+        self.id.format = match id.format {
             ELF_FORMAT_32BIT => ElfFormat::Bit32,
             ELF_FORMAT_64BIT => ElfFormat::Bit64,
             _ => return Err(Error::Fatal),
         };
 
-        self.id.endian = match data[ELF_ENDIAN_OFFSET] {
+        self.id.endian = match id.endian {
             ELF_ENDIAN_LITTLE => ElfEndian::Little,
             ELF_ENDIAN_BIG => ElfEndian::Big,
             _ => return Err(Error::Fatal),
         };
 
+        self.id.version = id.version;
+        self.id.osabi = id.osabi;
+        self.id.abi_version = id.abi_version;
+
+        self.hdr = match self.id.format {
+            ElfFormat::Bit32 => ElfHeader::Bit32(self.pull_hdr32(data)?),
+            _ => ElfHeader::Bit64(self.pull_hdr64(data)?),
+        };
+
         Ok(())
     }
 
+    fn pull_hdr32(&self, data: &[u8]) -> Result<Elf32Header, Error> {
+        let Some(raw) = RawElf32Header::read_from(data) else {
+            return Err(Error::Fatal);
+        };
+        let endian = &self.id.endian;
+        Ok(Elf32Header {
+            ftype: ElfFileType::from(endian.u16(raw.ftype)),
+            machine: endian.u16(raw.machine),
+            is_valid_version: endian.u32(raw.version) == ELF_VERSION_CURRENT,
+            entry: endian.u32(raw.entry),
+            segmenttbl: endian.u32(raw.segmenttbl),
+            sectiontbl: endian.u32(raw.sectiontbl),
+            flags: endian.u32(raw.flags),
+            hdrlen: endian.u16(raw.hdrlen),
+            segmentlen: endian.u16(raw.segmentlen),
+            segmentnr: endian.u16(raw.segmentnr),
+            sectionlen: endian.u16(raw.sectionlen),
+            sectionnr: endian.u16(raw.sectionnr),
+            sectionnametbl: endian.u16(raw.sectionnametbl),
+        })
+    }
+
+    fn pull_hdr64(&self, data: &[u8]) -> Result<Elf64Header, Error> {
+        let Some(raw) = RawElf64Header::read_from(data) else {
+            return Err(Error::Fatal);
+        };
+        let endian = &self.id.endian;
+        Ok(Elf64Header {
+            ftype: ElfFileType::from(endian.u16(raw.ftype)),
+            machine: endian.u16(raw.machine),
+            is_valid_version: endian.u32(raw.version) == ELF_VERSION_CURRENT,
+            entry: endian.u64(raw.entry),
+            segmenttbl: endian.u64(raw.segmenttbl),
+            sectiontbl: endian.u64(raw.sectiontbl),
+            flags: endian.u32(raw.flags),
+            hdrlen: endian.u16(raw.hdrlen),
+            segmentlen: endian.u16(raw.segmentlen),
+            segmentnr: endian.u16(raw.segmentnr),
+            sectionlen: endian.u16(raw.sectionlen),
+            sectionnr: endian.u16(raw.sectionnr),
+            sectionnametbl: endian.u16(raw.sectionnametbl),
+        })
+    }
+
+    fn pull_section(&self, data: &[u8], idx: usize) -> Result<ElfSection, Error> {
+        let (tbl, len) = match &self.hdr {
+            ElfHeader::Bit32(hdr) => (u64::from(hdr.sectiontbl), hdr.sectionlen),
+            ElfHeader::Bit64(hdr) => (hdr.sectiontbl, hdr.sectionlen),
+        };
+        let tbl = usize::try_from(tbl).map_err(|_| Error::Fatal)?;
+        let off = idx.checked_mul(usize::from(len))
+            .and_then(|off| off.checked_add(tbl))
+            .ok_or(Error::Fatal)?;
+        let Some(raw) = data.get(off..) else {
+            return Err(Error::Fatal);
+        };
+        let endian = &self.id.endian;
+        match &self.hdr {
+            ElfHeader::Bit32(_) => {
+                let Some(raw) = RawElf32Section::read_from(raw) else {
+                    return Err(Error::Fatal);
+                };
+                Ok(ElfSection::Bit32(Elf32Section {
+                    nameidx: endian.u32(raw.nameidx),
+                    stype: ElfSectionType::from(endian.u32(raw.stype)),
+                    flags: endian.u32(raw.flags),
+                    addr: endian.u32(raw.addr),
+                    align: endian.u32(raw.align),
+                    offset: endian.u32(raw.offset),
+                    len: u64::from(endian.u32(raw.len)),
+                    link: endian.u32(raw.link),
+                    info: endian.u32(raw.info),
+                    entrylen: endian.u32(raw.entrylen),
+                }))
+            },
+            ElfHeader::Bit64(_) => {
+                let Some(raw) = RawElf64Section::read_from(raw) else {
+                    return Err(Error::Fatal);
+                };
+                Ok(ElfSection::Bit64(Elf64Section {
+                    nameidx: endian.u32(raw.nameidx),
+                    stype: ElfSectionType::from(endian.u32(raw.stype)),
+                    flags: endian.u64(raw.flags),
+                    addr: endian.u64(raw.addr),
+                    align: endian.u64(raw.align),
+                    offset: endian.u64(raw.offset),
+                    len: endian.u64(raw.len),
+                    link: endian.u32(raw.link),
+                    info: endian.u32(raw.info),
+                    entrylen: endian.u64(raw.entrylen),
+                }))
+            },
+        }
+    }
+
+    pub fn find_section<'d>(&self, data: &'d [u8], name: &str) -> Result<&'d [u8], Error> {
+        let (nr, nametbl) = match &self.hdr {
+            ElfHeader::Bit32(hdr) => (hdr.sectionnr, hdr.sectionnametbl),
+            ElfHeader::Bit64(hdr) => (hdr.sectionnr, hdr.sectionnametbl),
+        };
+        let names = self.pull_section(data, usize::from(nametbl))?.data(data)?;
+        for idx in 0..usize::from(nr) {
+            let section = self.pull_section(data, idx)?;
+            let Some(raw) = names.get(section.nameidx()..) else {
+                continue;
+            };
+            let end = raw.iter().position(|byte| *byte == 0).unwrap_or(raw.len());
+            if &raw[..end] == name.as_bytes() {
+                return section.data(data);
+            }
+        }
+        Err(Error::NotFound)
+    }
+
     // fn get_magick(&self) -> u32 {
     //     let slice = &self.id[..];
     //     let (raw, _) = slice.split_at(4);
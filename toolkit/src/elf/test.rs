@@ -0,0 +1,87 @@
+extern crate std;
+
+use std::string::{ String };
+use std::vec::{ Vec };
+use crate::elf::{ ElfFormat, ElfHeader, ElfParser, Error };
+
+// 64 bit little endian riscv executable, no sections
+fn hdr64() -> Vec<u8> {
+    let mut hdr = Vec::new();
+    hdr.extend_from_slice(&[0x7f, b'E', b'L', b'F', 2, 1, 1, 0, 0]);
+    hdr.extend_from_slice(&[0; 7]);
+    hdr.extend_from_slice(&2u16.to_le_bytes());
+    hdr.extend_from_slice(&243u16.to_le_bytes());
+    hdr.extend_from_slice(&1u32.to_le_bytes());
+    hdr.extend_from_slice(&0x8000_0000u64.to_le_bytes());
+    hdr.extend_from_slice(&64u64.to_le_bytes());
+    hdr.extend_from_slice(&0x1000u64.to_le_bytes());
+    hdr.extend_from_slice(&0u32.to_le_bytes());
+    for half in [64u16, 56, 1, 64, 0, 0] {
+        hdr.extend_from_slice(&half.to_le_bytes());
+    }
+    hdr
+}
+
+// 32 bit big endian relocatable
+fn hdr32() -> Vec<u8> {
+    let mut hdr = Vec::new();
+    hdr.extend_from_slice(&[0x7f, b'E', b'L', b'F', 1, 2, 1, 0, 0]);
+    hdr.extend_from_slice(&[0; 7]);
+    hdr.extend_from_slice(&1u16.to_be_bytes());
+    hdr.extend_from_slice(&8u16.to_be_bytes());
+    hdr.extend_from_slice(&1u32.to_be_bytes());
+    hdr.extend_from_slice(&0x400u32.to_be_bytes());
+    hdr.extend_from_slice(&0u32.to_be_bytes());
+    hdr.extend_from_slice(&0x200u32.to_be_bytes());
+    hdr.extend_from_slice(&0u32.to_be_bytes());
+    for half in [52u16, 0, 0, 40, 3, 2] {
+        hdr.extend_from_slice(&half.to_be_bytes());
+    }
+    hdr
+}
+
+#[test]
+fn elf_header() {
+    let mut parser = ElfParser::new(String::new());
+    parser.pull(&hdr64()).unwrap();
+    assert!(matches!(parser.id.format, ElfFormat::Bit64));
+    let ElfHeader::Bit64(hdr) = &parser.hdr else {
+        panic!("expected a 64 bit header");
+    };
+    assert_eq!(hdr.machine, 243);
+    assert!(hdr.is_valid_version);
+    assert_eq!(hdr.entry, 0x8000_0000);
+    assert_eq!(hdr.sectiontbl, 0x1000);
+    assert_eq!((hdr.segmentnr, hdr.sectionlen), (1, 64));
+
+    let mut parser = ElfParser::new(String::new());
+    parser.pull(&hdr32()).unwrap();
+    let ElfHeader::Bit32(hdr) = &parser.hdr else {
+        panic!("expected a 32 bit header");
+    };
+    assert_eq!(hdr.machine, 8);
+    assert_eq!(hdr.entry, 0x400);
+    assert_eq!((hdr.sectiontbl, hdr.sectionnr, hdr.sectionnametbl), (0x200, 3, 2));
+}
+
+#[test]
+fn elf_bad_magic() {
+    let mut data = hdr64();
+    data[1] = b'X';
+    let mut parser = ElfParser::new(String::new());
+    assert!(matches!(parser.pull(&data), Err(Error::Fatal)));
+
+    let mut data = hdr64();
+    data[4] = 3;
+    assert!(matches!(parser.pull(&data), Err(Error::Fatal)));
+}
+
+#[test]
+fn elf_short() {
+    let data = hdr64();
+    let mut parser = ElfParser::new(String::new());
+    assert!(matches!(parser.pull(&data[..8]), Err(Error::Fatal)));
+    assert!(matches!(parser.pull(&data[..40]), Err(Error::Fatal)));
+    assert!(matches!(parser.pull(&[]), Err(Error::Fatal)));
+    assert!(parser.pull(&data).is_ok());
+}
@@ -3,8 +3,13 @@
 
 pub mod collection;
 pub mod bytebuf;
+pub mod register;
+pub mod uart;
 pub mod runtime;
 pub mod cmd;
+pub mod mem;
+pub mod sim;
+pub mod checksum;
 
 // pub mod virtio;
 
@@ -0,0 +1,5 @@
+pub mod frame;
+pub mod buddy;
+
+#[cfg(test)]
+mod test;
@@ -0,0 +1,169 @@
+use core::fmt::{ self };
+use toolkit_unsafe::{ IPCByteBuf };
+use toolkit_unsafe::registry::{ IPCRegistry };
+
+pub const MIN_ORDER: u32 = 9;
+pub const MAX_ORDER: u32 = 16;
+const ORDERNR: usize = (MAX_ORDER - MIN_ORDER + 1) as usize;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Error {
+    TooBig,
+    NoMem,
+    Full,
+}
+
+/*
+ * per order free list
+ */
+#[derive(Clone, Copy)]
+struct FreeList<const NR: usize> {
+    addr: [usize; NR],
+    len: usize,
+}
+
+impl<const NR: usize>
+FreeList<NR> {
+    fn new() -> Self {
+        Self {
+            addr: [0; NR],
+            len: 0,
+        }
+    }
+
+    fn is_full(&self) -> bool {
+        self.len == NR
+    }
+
+    fn iter(&self) -> impl Iterator<Item = &usize> {
+        self.addr[..self.len].iter()
+    }
+
+    fn push(&mut self, addr: usize) -> bool {
+        let Some(slot) = self.addr.get_mut(self.len) else {
+            return false;
+        };
+        *slot = addr;
+        self.len += 1;
+        true
+    }
+
+    fn pop(&mut self) -> Option<usize> {
+        self.len = self.len.checked_sub(1)?;
+        Some(self.addr[self.len])
+    }
+
+    fn contains(&self, addr: usize) -> bool {
+        self.iter().any(|free| *free == addr)
+    }
+
+    fn last(&self) -> Option<usize> {
+        self.iter().last().copied()
+    }
+
+    fn remove(&mut self, addr: usize) -> bool {
+        let Some(idx) = self.iter().position(|free| *free == addr) else {
+            return false;
+        };
+        self.len -= 1;
+        self.addr.swap(idx, self.len);
+        true
+    }
+}
+
+/*
+ * buddy allocator
+ */
+pub struct BuddyAlloc<'a, const NR: usize> {
+    free: [FreeList<NR>; ORDERNR],
+    registry: IPCRegistry<'a, 1, NR>,
+}
+
+impl<'a, const NR: usize>
+BuddyAlloc<'a, NR> {
+    // carving stops at the first block its free list cannot hold, the rest
+    // of `mem` is left unused
+    pub fn new(mem: IPCByteBuf<'a>) -> Self {
+        let mut free = [FreeList::new(); ORDERNR];
+        let end = mem.addr() + mem.len();
+        let mut addr = mem.addr().next_multiple_of(1 << MIN_ORDER);
+        while let Some(order) = (MIN_ORDER..=MAX_ORDER).rev().find(|order| {
+            addr.is_multiple_of(1 << order) && addr + (1 << order) <= end
+        }) {
+            if !free[(order - MIN_ORDER) as usize].push(addr) {
+                break;
+            }
+            addr += 1 << order;
+        }
+        Self {
+            free,
+            registry: IPCRegistry::from(mem),
+        }
+    }
+
+    fn order(len: usize) -> Result<u32, Error> {
+        let order = len.max(1).next_power_of_two().trailing_zeros().max(MIN_ORDER);
+        match order {
+            ..=MAX_ORDER => Ok(order),
+            _ => Err(Error::TooBig),
+        }
+    }
+
+    fn list(&mut self, order: u32) -> &mut FreeList<NR> {
+        &mut self.free[(order - MIN_ORDER) as usize]
+    }
+
+    pub fn alloc(&mut self, len: usize) -> Result<IPCByteBuf<'a>, Error> {
+        let order = Self::order(len)?;
+        let Some(top) = (order..=MAX_ORDER).find(|top| self.list(*top).len > 0) else {
+            return Err(Error::NoMem);
+        };
+        if (order..top).any(|split| self.list(split).is_full()) {
+            return Err(Error::Full);
+        }
+        let Some(addr) = self.list(top).last() else {
+            return Err(Error::NoMem);
+        };
+        let buf = self.registry.take(addr, 1 << order).map_err(|_| Error::Full)?;
+        self.list(top).pop();
+        for split in (order..top).rev() {
+            self.list(split).push(addr + (1 << split));
+        }
+        Ok(buf)
+    }
+
+    pub fn free(&mut self, buf: IPCByteBuf<'a>) -> Result<(), IPCByteBuf<'a>> {
+        let Ok(order) = Self::order(buf.len()) else {
+            return Err(buf);
+        };
+        // find where the merge ends before touching anything, so a full free
+        // list hands the block back instead of leaking it
+        let (mut top, mut merged) = (order, buf.addr());
+        while top < MAX_ORDER && self.list(top).contains(merged ^ (1 << top)) {
+            merged &= !(1 << top);
+            top += 1;
+        }
+        if self.list(top).is_full() {
+            return Err(buf);
+        }
+        let mut addr = buf.addr();
+        self.registry.give(buf)?;
+        for order in order..top {
+            self.list(order).remove(addr ^ (1 << order));
+            addr &= !(1 << order);
+        }
+        self.list(top).push(addr);
+        Ok(())
+    }
+
+    pub fn dump<W: fmt::Write>(&self, log: &mut W) -> fmt::Result {
+        for (order, list) in (MIN_ORDER..=MAX_ORDER).zip(self.free.iter()) {
+            write!(log, "buddy {:>6}: {} free", 1usize << order, list.len)?;
+            for addr in list.iter() {
+                write!(log, " {:#x}", addr)?;
+            }
+            writeln!(log)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,96 @@
+use toolkit_unsafe::{ IPCByteBuf };
+use toolkit_unsafe::registry::{ self, IPCRegistry, MemRegion };
+
+pub const PAGE_LEN: usize = 4096;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Error {
+    NoMem,
+    Full,
+    Invalid,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct FrameStats {
+    pub total: usize,
+    pub used: usize,
+    pub free: usize,
+    pub allocs: usize,
+}
+
+pub struct FrameAlloc<'a, const NR: usize> {
+    base: usize,
+    framenr: usize,
+    used: usize,
+    registry: IPCRegistry<'a, 1, NR>,
+}
+
+impl<'a, const NR: usize>
+FrameAlloc<'a, NR> {
+    pub fn new(mem: IPCByteBuf<'a>) -> Self {
+        let end = mem.addr() + mem.len();
+        let base = mem.addr().checked_next_multiple_of(PAGE_LEN).unwrap_or(end);
+        Self {
+            base,
+            framenr: end.saturating_sub(base) / PAGE_LEN,
+            used: 0,
+            registry: IPCRegistry::from(mem),
+        }
+    }
+
+    pub fn stats(&self) -> FrameStats {
+        FrameStats {
+            total: self.framenr,
+            used: self.used,
+            free: self.framenr - self.used,
+            allocs: self.registry.taken().count(),
+        }
+    }
+
+    pub fn alloc(&mut self) -> Result<IPCByteBuf<'a>, Error> {
+        self.alloc_contiguous(1, PAGE_LEN)
+    }
+
+    pub fn alloc_contiguous(&mut self, nr: usize, align: usize) -> Result<IPCByteBuf<'a>, Error> {
+        // any power of two at or above PAGE_LEN keeps frames page aligned
+        if nr == 0 || !align.is_power_of_two() {
+            return Err(Error::Invalid);
+        }
+        let align = align.max(PAGE_LEN);
+        let Some(len) = nr.checked_mul(PAGE_LEN) else {
+            return Err(Error::NoMem);
+        };
+        let end = self.base + self.framenr * PAGE_LEN;
+        let mut addr = self.base.checked_next_multiple_of(align);
+        let addr = loop {
+            let Some(start) = addr else {
+                return Err(Error::NoMem);
+            };
+            let region = MemRegion::new(start, len);
+            if region.end().is_none_or(|region_end| region_end > end) {
+                return Err(Error::NoMem);
+            }
+            let next = self.registry.taken()
+                .filter(|taken| taken.overlaps(&region))
+                .filter_map(|taken| taken.end())
+                .max();
+            match next {
+                None => break start,
+                Some(next) => addr = next.checked_next_multiple_of(align),
+            }
+        };
+        let buf = self.registry.take(addr, len).map_err(|err| match err {
+            registry::Error::Full => Error::Full,
+            _ => Error::NoMem,
+        })?;
+        self.used += nr;
+        Ok(buf)
+    }
+
+    pub fn free(&mut self, buf: IPCByteBuf<'a>) -> Result<(), IPCByteBuf<'a>> {
+        let nr = buf.len() / PAGE_LEN;
+        self.registry.give(buf)?;
+        self.used -= nr;
+        Ok(())
+    }
+}
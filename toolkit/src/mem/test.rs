@@ -0,0 +1,125 @@
+extern crate std;
+
+use std::{ format };
+use crate::mem::frame::{ self, FrameAlloc, FrameStats, PAGE_LEN };
+use crate::mem::buddy::{ self, BuddyAlloc };
+use crate::bytebuf::stream::{ ByteWriter };
+use toolkit_unsafe::{ IPCByteBuf };
+
+const PAGENR: usize = 16;
+
+#[repr(align(65536))]
+struct Pages([u8; PAGENR * PAGE_LEN]);
+
+#[repr(align(65536))]
+struct Blocks([u8; 3 << buddy::MAX_ORDER]);
+
+#[test]
+fn frame_alloc_free() {
+    let mut pages = Pages([0; PAGENR * PAGE_LEN]);
+    let base = pages.0.as_ptr().addr();
+    let mut frames = FrameAlloc::<8>::new(IPCByteBuf::from(&mut pages.0[PAGE_LEN / 2..]));
+
+    // the first, partial page is skipped
+    assert_eq!(frames.stats(), FrameStats {
+        total: PAGENR - 1, used: 0, free: PAGENR - 1, allocs: 0,
+    });
+
+    let first = frames.alloc().unwrap();
+    assert_eq!(first.addr(), base + PAGE_LEN);
+    assert_eq!(first.len(), PAGE_LEN);
+
+    let run = frames.alloc_contiguous(3, 4 * PAGE_LEN).unwrap();
+    assert_eq!(run.addr(), base + 4 * PAGE_LEN);
+    assert_eq!(run.len(), 3 * PAGE_LEN);
+
+    let second = frames.alloc().unwrap();
+    assert_eq!(second.addr(), base + 2 * PAGE_LEN);
+    assert_eq!(frames.stats().used, 5);
+    assert_eq!(frames.stats().allocs, 3);
+
+    assert_eq!(frames.alloc_contiguous(10, PAGE_LEN).err(), Some(frame::Error::NoMem));
+    assert_eq!(frames.alloc_contiguous(0, PAGE_LEN).err(), Some(frame::Error::Invalid));
+    assert_eq!(frames.alloc_contiguous(1, 0).err(), Some(frame::Error::Invalid));
+    assert_eq!(frames.alloc_contiguous(1, 3 * PAGE_LEN).err(), Some(frame::Error::Invalid));
+    assert_eq!(frames.alloc_contiguous(1, PAGE_LEN + 8).err(), Some(frame::Error::Invalid));
+    assert_eq!(frames.stats().allocs, 3);
+
+    assert!(frames.free(run).is_ok());
+    assert!(frames.free(IPCByteBuf::default()).is_err());
+    assert_eq!(frames.stats().used, 2);
+
+    let run = frames.alloc_contiguous(10, PAGE_LEN).unwrap();
+    assert_eq!(run.addr(), base + 3 * PAGE_LEN);
+
+    assert!(frames.free(first).is_ok());
+    assert!(frames.free(second).is_ok());
+    assert!(frames.free(run).is_ok());
+    assert_eq!(frames.stats(), FrameStats {
+        total: PAGENR - 1, used: 0, free: PAGENR - 1, allocs: 0,
+    });
+}
+
+#[test]
+fn buddy_alloc_free() {
+    let mut blocks = Blocks([0; 3 << buddy::MAX_ORDER]);
+    let base = blocks.0.as_ptr().addr();
+    let mut buddy = BuddyAlloc::<8>::new(IPCByteBuf::from(&mut blocks.0[512..]));
+
+    let first = buddy.alloc(100).unwrap();
+    assert_eq!(first.addr(), base + 512);
+    assert_eq!(first.len(), 512);
+
+    // splits the 1K block, leaving its upper half free
+    let second = buddy.alloc(512).unwrap();
+    assert_eq!(second.addr(), base + 1024);
+
+    let third = buddy.alloc(512).unwrap();
+    assert_eq!(third.addr(), base + 1536);
+
+    let big = buddy.alloc(1 << buddy::MAX_ORDER).unwrap();
+    assert_eq!(big.addr() % (1 << buddy::MAX_ORDER), 0);
+    assert_eq!(buddy.alloc((1 << buddy::MAX_ORDER) + 1).err(), Some(buddy::Error::TooBig));
+
+    assert!(buddy.free(IPCByteBuf::default()).is_err());
+    assert!(buddy.free(third).is_ok());
+    assert!(buddy.free(first).is_ok());
+    assert!(buddy.free(second).is_ok());
+    assert!(buddy.free(big).is_ok());
+
+    let mut raw = [0u8; 512];
+    let mut log = ByteWriter::new(&mut raw[..]);
+    assert_eq!(buddy.dump(&mut log), Ok(()));
+    let len = log.pos();
+    let dump = core::str::from_utf8(&raw[..len]).unwrap();
+
+    let mut lines = dump.lines();
+    assert_eq!(lines.next(), Some(format!("buddy    512: 1 free {:#x}", base + 512).as_str()));
+    assert_eq!(lines.next(), Some(format!("buddy   1024: 1 free {:#x}", base + 1024).as_str()));
+    assert_eq!(lines.last(), Some(format!(
+        "buddy  65536: 2 free {:#x} {:#x}", base + (1 << 16), base + (2 << 16),
+    ).as_str()));
+}
+
+#[test]
+fn buddy_overflow() {
+    let mut blocks = Blocks([0; 3 << buddy::MAX_ORDER]);
+    let base = blocks.0.as_ptr().addr();
+    let top = 1 << buddy::MAX_ORDER;
+    // three top order blocks, but only room to track two of them
+    let mut buddy = BuddyAlloc::<2>::new(IPCByteBuf::from(&mut blocks.0[..]));
+
+    let first = buddy.alloc(top).unwrap();
+    let second = buddy.alloc(top).unwrap();
+    assert_eq!(first.addr(), base + top);
+    assert_eq!(second.addr(), base);
+    assert_eq!(buddy.alloc(512).err(), Some(buddy::Error::NoMem));
+
+    assert!(buddy.free(first).is_ok());
+    assert!(buddy.free(second).is_ok());
+
+    let again = buddy.alloc(512).unwrap();
+    assert_eq!(again.addr(), base);
+    assert!(buddy.free(again).is_ok());
+    assert_eq!(buddy.alloc(top).map(|buf| buf.addr()), Ok(base));
+}
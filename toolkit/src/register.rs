@@ -0,0 +1,251 @@
+use core::marker::{ PhantomData };
+use core::mem::{ size_of };
+use core::time::{ Duration };
+use crate::bytebuf::{ VolatileByteBuf, Error };
+use crate::runtime::{ Time };
+
+#[cfg(test)]
+mod test;
+
+pub trait RegWidth: Copy {
+    fn rd<B: VolatileByteBuf + ?Sized>(io: &mut B, off: usize) -> Self;
+    fn wr<B: VolatileByteBuf + ?Sized>(io: &mut B, off: usize, value: Self);
+
+    fn into_u64(self) -> u64;
+    fn from_u64(value: u64) -> Self;
+}
+
+macro_rules! reg_width {
+    ($width:ty, $rd:ident, $wr:ident) => {
+        impl RegWidth for $width {
+            fn rd<B: VolatileByteBuf + ?Sized>(io: &mut B, off: usize) -> Self {
+                io.$rd(off)
+            }
+
+            fn wr<B: VolatileByteBuf + ?Sized>(io: &mut B, off: usize, value: Self) {
+                io.$wr(off, value);
+            }
+
+            fn into_u64(self) -> u64 {
+                self as u64
+            }
+
+            fn from_u64(value: u64) -> Self {
+                value as $width
+            }
+        }
+    };
+}
+
+reg_width!(u8, rd8_volatile, wr8_volatile);
+reg_width!(u16, rd16_volatile, wr16_volatile);
+reg_width!(u32, rd32_volatile, wr32_volatile);
+reg_width!(u64, rd64_volatile, wr64_volatile);
+
+/*
+ * access kinds
+ */
+pub struct RO;
+pub struct WO;
+pub struct RW;
+
+pub trait Readable { }
+pub trait Writable { }
+
+impl Readable for RO { }
+impl Readable for RW { }
+impl Writable for WO { }
+impl Writable for RW { }
+
+/*
+ * register field
+ */
+#[derive(Clone, Copy, Debug)]
+pub struct Field<W> {
+    shift: u32,
+    mask: u64,
+    width: PhantomData<W>,
+}
+
+impl<W> Field<W> {
+    // evaluated in a const item, a field outside the register fails the build
+    pub const fn new(hi: u32, lo: u32) -> Self {
+        assert!(lo <= hi && (hi as usize) < size_of::<W>() * 8, "field exceeds register width");
+        let bits = hi - lo + 1;
+        Self {
+            shift: lo,
+            mask: match bits {
+                64.. => u64::MAX,
+                _ => (1 << bits) - 1,
+            },
+            width: PhantomData,
+        }
+    }
+
+    pub fn shift(&self) -> u32 {
+        self.shift
+    }
+}
+
+impl<W> Field<W>
+where W: RegWidth {
+    pub fn mask(&self) -> W {
+        W::from_u64(self.mask << self.shift)
+    }
+
+    pub fn get(&self, reg: W) -> W {
+        W::from_u64((reg.into_u64() >> self.shift) & self.mask)
+    }
+
+    pub fn set(&self, reg: W, value: W) -> W {
+        let mask = self.mask << self.shift;
+        let value = (value.into_u64() & self.mask) << self.shift;
+        W::from_u64((reg.into_u64() & !mask) | value)
+    }
+}
+
+/*
+ * register accessor
+ */
+pub struct Reg<'a, B: ?Sized, W, A> {
+    io: &'a mut B,
+    off: usize,
+    kind: PhantomData<(W, A)>,
+}
+
+impl<'a, B: ?Sized, W, A>
+Reg<'a, B, W, A> {
+    pub fn new(io: &'a mut B, off: usize) -> Self {
+        Self {
+            io, off,
+            kind: PhantomData,
+        }
+    }
+
+    pub fn offset(&self) -> usize {
+        self.off
+    }
+}
+
+impl<B: ?Sized, W, A>
+Reg<'_, B, W, A>
+where B: VolatileByteBuf, W: RegWidth, A: Readable {
+    pub fn read(&mut self) -> W {
+        W::rd(self.io, self.off)
+    }
+
+    pub fn read_field(&mut self, field: Field<W>) -> W {
+        field.get(self.read())
+    }
+
+    pub fn is_set(&mut self, field: Field<W>) -> bool {
+        self.read_field(field).into_u64() != 0
+    }
+
+    pub fn wait_field<T: Time>(
+        &mut self, time: &mut T, field: Field<W>, value: W, timeout: Duration,
+    ) -> Result<W, Error> {
+        let value = field.set(W::from_u64(0), value);
+        self.io.wait_until(time, self.off, field.mask(), value, timeout)
+    }
+}
+
+impl<B: ?Sized, W, A>
+Reg<'_, B, W, A>
+where B: VolatileByteBuf, W: RegWidth, A: Writable {
+    pub fn write(&mut self, value: W) {
+        W::wr(self.io, self.off, value);
+    }
+
+    pub fn write_field(&mut self, field: Field<W>, value: W) {
+        self.write(field.set(W::from_u64(0), value));
+    }
+}
+
+impl<B: ?Sized, W, A>
+Reg<'_, B, W, A>
+where B: VolatileByteBuf, W: RegWidth, A: Readable + Writable {
+    pub fn modify<F: FnOnce(W) -> W>(&mut self, f: F) {
+        let value = f(self.read());
+        self.write(value);
+    }
+
+    pub fn modify_field(&mut self, field: Field<W>, value: W) {
+        self.modify(|reg| field.set(reg, value));
+    }
+}
+
+/*
+ * register block definition
+ */
+/// Fields outside their register are rejected at compile time:
+///
+/// ```compile_fail
+/// toolkit::register_block! {
+///     struct Bad {
+///         0x00 => ctrl: u8, RW { OVER[8:7] },
+///     }
+/// }
+/// ```
+#[macro_export]
+macro_rules! register_block {
+    (@lo $hi:literal) => { $hi };
+    (@lo $hi:literal : $lo:literal) => { $lo };
+    (
+        $(#[$meta:meta])*
+        $vis:vis struct $name:ident {
+            $(
+                $off:literal => $reg:ident: $width:ty, $access:ident $({
+                    $($field:ident [$hi:literal $(: $lo:literal)?]),* $(,)?
+                })?
+            ),* $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        $vis struct $name<B> {
+            io: B,
+        }
+
+        // a private block need not touch every register
+        #[allow(dead_code)]
+        impl<B> $name<B>
+        where B: $crate::bytebuf::VolatileByteBuf {
+            pub fn new(io: B) -> Self {
+                Self {
+                    io,
+                }
+            }
+
+            pub fn into_inner(self) -> B {
+                self.io
+            }
+
+            pub fn get_mut(&mut self) -> &mut B {
+                &mut self.io
+            }
+
+            $(
+                pub fn $reg(&mut self)
+                -> $crate::register::Reg<'_, B, $width, $crate::register::$access> {
+                    $crate::register::Reg::new(&mut self.io, $off)
+                }
+            )*
+        }
+
+        $(
+            // widths are resolved from the block's own module
+            #[allow(dead_code)]
+            $vis mod $reg {
+                #[allow(unused_imports)]
+                use super::*;
+
+                pub const OFFSET: usize = $off;
+                $($(
+                    pub const $field: $crate::register::Field<$width> = $crate::register::Field::new(
+                        $hi, $crate::register_block!(@lo $hi $(: $lo)?),
+                    );
+                )*)?
+            }
+        )*
+    };
+}
@@ -0,0 +1,58 @@
+use crate::register::{ Field };
+use crate::register_block;
+
+type Word = u16;
+
+register_block! {
+    struct Dev {
+        0x00 => ctrl: Word, RW {
+            EN[0],
+            MODE[3:1],
+            TOP[15],
+        },
+        0x02 => status: u16, RO {
+            BUSY[0],
+        },
+        0x04 => data: u32, WO,
+        0x08 => wide: u64, RW {
+            ALL[63:0],
+            HI[63:32],
+        },
+    }
+}
+
+#[test]
+fn register_field() {
+    let field: Field<u16> = Field::new(3, 1);
+    assert_eq!(field.shift(), 1);
+    assert_eq!(field.mask(), 0b1110);
+    assert_eq!(field.get(0b1010), 0b101);
+    assert_eq!(field.set(0xffff, 0), 0xfff1);
+    // values wider than the field are masked rather than spilling over
+    assert_eq!(field.set(0, 0xff), 0b1110);
+
+    assert_eq!(wide::ALL.mask(), u64::MAX);
+    assert_eq!(wide::HI.get(0x1234_5678_0000_0000), 0x1234_5678);
+}
+
+#[test]
+fn register_block() {
+    assert_eq!((ctrl::OFFSET, status::OFFSET, data::OFFSET, wide::OFFSET), (0, 2, 4, 8));
+
+    let mut dev = Dev::new([0u8; 16]);
+    dev.ctrl().write_field(ctrl::MODE, 5);
+    dev.ctrl().modify_field(ctrl::EN, 1);
+    dev.ctrl().modify_field(ctrl::TOP, 1);
+    assert_eq!(dev.ctrl().read(), 0x800b);
+    assert!(dev.ctrl().is_set(ctrl::EN));
+    assert_eq!(dev.ctrl().read_field(ctrl::MODE), 5);
+    assert!(!dev.status().is_set(status::BUSY));
+
+    dev.data().write(0xdead_beef);
+    dev.wide().modify_field(wide::HI, 0xcafe);
+
+    let mem = dev.into_inner();
+    assert_eq!(mem[..2], 0x800bu16.to_ne_bytes());
+    assert_eq!(mem[4..8], 0xdead_beefu32.to_ne_bytes());
+    assert_eq!(mem[8..], 0xcafe_0000_0000u64.to_ne_bytes());
+}
@@ -1,99 +1,385 @@
 use core::fmt::{ self, Write };
 use core::cell::{ Cell, RefCell };
 use core::borrow::{ Borrow, BorrowMut };
+use core::marker::{ PhantomData };
+use core::mem::{ size_of };
 use core::time::{ Duration };
-use crate::collection::deque::{ Deque, DequeRefIter, DequeMutRefIter };
-use crate::cmd::{ Queue };
+use crate::collection::deque::{ Deque };
+use crate::cmd::{ Poll, Queue };
 use toolkit_unsafe::{ IPCByteBuf };
 
+use self::log::{ DEFAULT_LEVEL, Level, LogBufBuf, LogRecord };
+use self::sink::{ self as logsink, LogSink };
+
+pub mod log;
+pub mod sink;
+pub mod binlog;
+pub mod exec;
+pub mod time;
+#[cfg(target_has_atomic = "8")]
+pub mod sync;
+#[cfg(test)]
+mod test;
+
+#[cfg(feature = "alloc")]
+use toolkit_unsafe::heap::{ Heap, HeapStats };
+
 pub trait Time {
     fn time(&mut self) -> Duration;
 }
 
+impl<T> Time for &mut T
+where T: Time + ?Sized {
+    fn time(&mut self) -> Duration {
+        (**self).time()
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Error {
+    NoBuf,
+    Busy,
+    OutOfBounds,
+    Unaligned,
+}
+
+macro_rules! ipcbuf_access {
+    ($(
+        $ty:ty, $bits:literal: $rd:ident, $wr:ident, $rdv:ident, $wrv:ident,
+        $rdo:ident, $wro:ident, $rda:ident, $wra:ident;
+    )*) => {
+        $(
+            fn $rd(&self, off: usize) -> Result<$ty, Error> {
+                self.with_ipcbuf(off, size_of::<$ty>(), |buf| buf.$rd(0))
+            }
+
+            fn $wr(&mut self, off: usize, value: $ty) -> Result<(), Error> {
+                self.with_ipcbuf(off, size_of::<$ty>(), |buf| buf.$wr(0, value))
+            }
+
+            fn $rdv(&self, off: usize) -> Result<$ty, Error> {
+                self.with_ipcbuf(off, size_of::<$ty>(), |buf| buf.$rdv(0))
+            }
+
+            fn $wrv(&mut self, off: usize, value: $ty) -> Result<(), Error> {
+                self.with_ipcbuf(off, size_of::<$ty>(), |buf| buf.$wrv(0, value))
+            }
+
+            fn $rdo(&self, off: usize) -> Result<$ty, Error> {
+                self.with_ipcbuf(off, size_of::<$ty>(), |buf| buf.$rdo(0))
+            }
+
+            fn $wro(&mut self, off: usize, value: $ty) -> Result<(), Error> {
+                self.with_ipcbuf(off, size_of::<$ty>(), |buf| buf.$wro(0, value))
+            }
+
+            #[cfg(target_has_atomic = $bits)]
+            fn $rda(&self, off: usize) -> Result<$ty, Error> {
+                self.with_ipcbuf(off, size_of::<$ty>(), |buf| {
+                    buf.addr().is_multiple_of(size_of::<$ty>()).then(|| buf.$rda(0))
+                })?.ok_or(Error::Unaligned)
+            }
+
+            #[cfg(target_has_atomic = $bits)]
+            fn $wra(&mut self, off: usize, value: $ty) -> Result<(), Error> {
+                self.with_ipcbuf(off, size_of::<$ty>(), |buf| {
+                    buf.addr().is_multiple_of(size_of::<$ty>()).then(|| buf.$wra(0, value))
+                })?.ok_or(Error::Unaligned)
+            }
+        )*
+    };
+}
+
 pub trait Runtime: Time + fmt::Write {
+    type Response;
+    type QueueError;
+
     fn logbuf(&mut self, idx: usize);
+    fn level(&self) -> Option<Level>;
+    fn set_level(&mut self, chan: usize, level: Option<Level>);
+
+    fn enabled(&self, level: Level) -> bool {
+        self.level().is_some_and(|max| level <= max)
+    }
+
+    fn log(&mut self, level: Level, args: fmt::Arguments<'_>);
+    fn log_binary(&mut self, level: Level, payload: &[u8]);
+
     fn ipcbuf(&mut self, idx: usize);
 
-    fn wr8(&mut self, off: usize, value: u8);
-    fn rd8(&self, off: usize) -> u8;
+    // runs f on the off..off + len window of the selected ipc buffer
+    fn with_ipcbuf<R, F>(&self, off: usize, len: usize, f: F) -> Result<R, Error>
+    where F: FnOnce(&mut IPCByteBuf<'_>) -> R;
+
+    ipcbuf_access! {
+        u8, "8": rd8, wr8, rd8_volatile, wr8_volatile,
+            rd8_volatile_ordered, wr8_volatile_ordered, rd8_atomic, wr8_atomic;
+        u16, "16": rd16, wr16, rd16_volatile, wr16_volatile,
+            rd16_volatile_ordered, wr16_volatile_ordered, rd16_atomic, wr16_atomic;
+        u32, "32": rd32, wr32, rd32_volatile, wr32_volatile,
+            rd32_volatile_ordered, wr32_volatile_ordered, rd32_atomic, wr32_atomic;
+        u64, "64": rd64, wr64, rd64_volatile, wr64_volatile,
+            rd64_volatile_ordered, wr64_volatile_ordered, rd64_atomic, wr64_atomic;
+    }
+
+    // the selected ipc buffer index is the request payload, a queue that
+    // is already in use reads as pending. the buffer reads as Busy until
+    // poll returns its completion
+    fn submit(&mut self) -> Poll<Result<(), Self::QueueError>>;
+    fn poll(&mut self) -> Poll<Result<Self::Response, Self::QueueError>>;
+
+    #[cfg(feature = "alloc")]
+    fn heap_stats(&self) -> Option<HeapStats>;
+}
+
+/*
+ * shared runtime state, None while the calling hart holds it further up
+ * the stack
+ */
+trait State<'a, T, Q, const BUFNR: usize, const CHL: usize, const CHNR: usize> {
+    fn with_timer<R, F: FnOnce(&mut T) -> R>(&self, hart: usize, f: F) -> Option<R>;
+    fn with_queue<R, F: FnOnce(&mut Q) -> R>(&self, hart: usize, f: F) -> Option<R>;
+    fn with_logbufbuf<R, F: FnOnce(&mut LogBufBuf<CHL, CHNR>) -> R>(&self, hart: usize, f: F) -> Option<R>;
+    fn with_ipcbufbuf<R, F: FnOnce(&mut IPCBufBuf<'a, BUFNR>) -> R>(&self, hart: usize, f: F) -> Option<R>;
+
+    #[cfg(feature = "alloc")]
+    fn heap(&self, hart: usize) -> Option<&'a Heap>;
+}
+
+/*
+ * ipc buffers, a submitted buffer belongs to the device until its completion
+ * is polled. completions carry no buffer index, so only one buffer is in
+ * flight at a time and there is no doubt which one came back
+ */
+struct IPCBufBuf<'a, const BUFNR: usize> {
+    bufs: Deque<IPCByteBuf<'a>, BUFNR>,
+    inflight: Option<usize>,
+}
+
+impl<'a, const BUFNR: usize>
+IPCBufBuf<'a, BUFNR> {
+    fn new<B: FnMut(usize) -> IPCByteBuf<'a>>(bufctr: B) -> Self {
+        Self {
+            bufs: Deque::full(bufctr),
+            inflight: None,
+        }
+    }
+
+    fn claim(&mut self, idx: usize) -> bool {
+        if self.inflight.is_some() || idx >= self.bufs.len() {
+            return false;
+        }
+        self.inflight = Some(idx);
+        true
+    }
+
+    fn release(&mut self) {
+        self.inflight = None;
+    }
+}
+
+fn for_each_record<F, const CHL: usize, const CHNR: usize>(
+    logbufbuf: &LogBufBuf<CHL, CHNR>, chan: usize, mut f: F,
+)
+where F: FnMut(&LogRecord) {
+    if let Some(buf) = logbufbuf.iter().nth(chan) {
+        buf.records().for_each(|rec| f(&rec));
+    }
+}
+
+// records are only consumed once the sink accepted them
+fn drain<S, const CHL: usize, const CHNR: usize>(
+    logbufbuf: &mut LogBufBuf<CHL, CHNR>, chan: usize, mut sink: S,
+) -> Result<usize, logsink::Error>
+where S: LogSink {
+    let Some(buf) = logbufbuf.iter_mut().nth(chan) else {
+        return Ok(0);
+    };
+    let mut nr = 0;
+    while let Some(rec) = buf.peek() {
+        sink.write(&rec)?;
+        buf.pop();
+        nr += 1;
+    }
+    Ok(nr)
+}
+
+fn drain_all<S, const CHL: usize, const CHNR: usize>(
+    logbufbuf: &mut LogBufBuf<CHL, CHNR>, mut sink: S,
+) -> Result<usize, logsink::Error>
+where S: LogSink {
+    let mut nr = 0;
+    loop {
+        // oldest record first across all channels
+        let next = logbufbuf.iter_mut()
+            .filter_map(|buf| buf.peek().map(|rec| (rec, buf)))
+            .min_by_key(|(rec, _)| rec.time);
+        let Some((rec, buf)) = next else {
+            return Ok(nr);
+        };
+        sink.write(&rec)?;
+        buf.pop();
+        nr += 1;
+    }
 }
 
 /*
  * runtime reference
  */
 #[derive(Clone, Copy)]
-struct RuntimeRef<'a, T, Q, const BUFNR: usize, const CHL: usize, const CHNR: usize> {
+struct RuntimeRef<'a, S, T, Q, const BUFNR: usize, const CHL: usize, const CHNR: usize> {
+    hart: usize,
     logbuf: usize,
     ipcbuf: usize,
-    rt: &'a RuntimeMain<'a, T, Q, BUFNR, CHL, CHNR>,
+    rt: &'a S,
+    state: PhantomData<fn() -> (T, Q)>,
 }
 
-impl<'a, T, Q, const BUFNR: usize, const CHL: usize, const CHNR: usize>
-Time for RuntimeRef<'a, T, Q, BUFNR, CHL, CHNR>
-where T: Time {
+impl<'a, S, T, Q, const BUFNR: usize, const CHL: usize, const CHNR: usize>
+RuntimeRef<'a, S, T, Q, BUFNR, CHL, CHNR> {
+    fn new(rt: &'a S, hart: usize) -> Self {
+        Self {
+            hart, logbuf: 0, ipcbuf: 0, rt,
+            state: PhantomData,
+        }
+    }
+}
+
+impl<'a, S, T, Q, const BUFNR: usize, const CHL: usize, const CHNR: usize>
+Time for RuntimeRef<'a, S, T, Q, BUFNR, CHL, CHNR>
+where S: State<'a, T, Q, BUFNR, CHL, CHNR>, T: Time {
     fn time(&mut self) -> Duration {
-        let Some(mut timer) = self.rt.timer.take() else {
-            return Duration::default();
-        };
-        let time = timer.time();
-        self.rt.timer.set(Some(timer));
-        time
+        self.rt.with_timer(self.hart, |timer| timer.time()).unwrap_or_default()
     }
 }
 
-impl<'a, T, Q, const BUFNR: usize, const CHL: usize, const CHNR: usize>
-fmt::Write for RuntimeRef<'a, T, Q, BUFNR, CHL, CHNR> {
+impl<'a, S, T, Q, const BUFNR: usize, const CHL: usize, const CHNR: usize>
+fmt::Write for RuntimeRef<'a, S, T, Q, BUFNR, CHL, CHNR>
+where S: State<'a, T, Q, BUFNR, CHL, CHNR>, T: Time, Q: Queue<Request = usize> {
+    // raw writes are filtered like any other record at DEFAULT_LEVEL
     fn write_str(&mut self, s: &str) -> Result<(), fmt::Error> {
-        if let Some(buf) = self.rt.logbufbuf.borrow_mut().iter_mut().nth(self.logbuf) {
-            buf.write_str(s);
-        }
+        self.log(DEFAULT_LEVEL, format_args!("{}", s));
+        Ok(())
+    }
+
+    // one record per write!/writeln! rather than per formatted piece
+    fn write_fmt(&mut self, args: fmt::Arguments<'_>) -> Result<(), fmt::Error> {
+        self.log(DEFAULT_LEVEL, args);
         Ok(())
     }
 }
 
-impl<'a, T, Q, const BUFNR: usize, const CHL: usize, const CHNR: usize>
-Runtime for RuntimeRef<'a, T, Q, BUFNR, CHL, CHNR>
-where T: Time {
+impl<'a, S, T, Q, const BUFNR: usize, const CHL: usize, const CHNR: usize>
+RuntimeRef<'a, S, T, Q, BUFNR, CHL, CHNR>
+where S: State<'a, T, Q, BUFNR, CHL, CHNR>, T: Time {
+    fn record(&mut self, level: Level, args: fmt::Arguments<'_>) {
+        let mut rec = LogRecord::new(self.time(), level, self.logbuf);
+        let _ = rec.write_fmt(args);
+        rec.trim_newline();
+        self.push(&rec);
+    }
+
+    fn push(&self, rec: &LogRecord) {
+        self.rt.with_logbufbuf(self.hart, |bufs| {
+            if let Some(buf) = bufs.iter_mut().nth(self.logbuf) {
+                buf.push(rec);
+            }
+        });
+    }
+}
+
+impl<'a, S, T, Q, const BUFNR: usize, const CHL: usize, const CHNR: usize>
+Runtime for RuntimeRef<'a, S, T, Q, BUFNR, CHL, CHNR>
+where S: State<'a, T, Q, BUFNR, CHL, CHNR>, T: Time, Q: Queue<Request = usize> {
+    type Response = Q::Response;
+    type QueueError = Q::Error;
+
     fn logbuf(&mut self, idx: usize) {
-        // let logbuf = self.rt.logbufbuf.take();
-        // if idx < logbuf.iter().len() {
-        //     self.logbuf = idx;
-        // }
-        // self.rt.logbufbuf.set(logbuf);
-        let buf = self.rt.logbufbuf.borrow();
-        if idx < buf.iter().len() {
+        if self.rt.with_logbufbuf(self.hart, |bufs| idx < bufs.iter().len()).unwrap_or(false) {
             self.logbuf = idx;
         }
     }
 
-    fn ipcbuf(&mut self, idx: usize) {
-        let ipcbufbuf = self.rt.ipcbufbuf.take();
-        if let Some(buf) = &ipcbufbuf {
-            if idx < buf.iter().len() {
-                self.ipcbuf = idx;
+    fn log(&mut self, level: Level, args: fmt::Arguments<'_>) {
+        if self.enabled(level) {
+            self.record(level, args);
+        }
+    }
+
+    fn log_binary(&mut self, level: Level, payload: &[u8]) {
+        if !self.enabled(level) {
+            return;
+        }
+        let mut rec = LogRecord::new(self.time(), level, self.logbuf);
+        rec.binary = true;
+        rec.push_bytes(payload);
+        self.push(&rec);
+    }
+
+    fn level(&self) -> Option<Level> {
+        self.rt.with_logbufbuf(self.hart, |bufs| bufs.iter().nth(self.logbuf).and_then(|buf| buf.level))?
+    }
+
+    fn set_level(&mut self, chan: usize, level: Option<Level>) {
+        self.rt.with_logbufbuf(self.hart, |bufs| {
+            if let Some(buf) = bufs.iter_mut().nth(chan) {
+                buf.level = level;
             }
+        });
+    }
+
+    fn ipcbuf(&mut self, idx: usize) {
+        if self.rt.with_ipcbufbuf(self.hart, |bufs| idx < bufs.bufs.len()).unwrap_or(false) {
+            self.ipcbuf = idx;
         }
-        self.rt.ipcbufbuf.set(ipcbufbuf);
     }
 
-    fn rd8(&self, off: usize) -> u8 {
-        let Some(inner) = self.rt.ipcbufbuf.take() else {
-            return 0;
-        };
-        let Some(ipcbuf) = inner.iter().nth(self.ipcbuf) else {
-            return 0;
+    fn with_ipcbuf<R, F>(&self, off: usize, len: usize, f: F) -> Result<R, Error>
+    where F: FnOnce(&mut IPCByteBuf<'_>) -> R {
+        let Some(ret) = self.rt.with_ipcbufbuf(self.hart, |bufs| {
+            if bufs.inflight == Some(self.ipcbuf) {
+                return Err(Error::Busy);
+            }
+            let Some(ipcbuf) = bufs.bufs.get_mut(self.ipcbuf) else {
+                return Err(Error::NoBuf);
+            };
+            let end = off.checked_add(len).ok_or(Error::OutOfBounds)?;
+            let Some(mut window) = ipcbuf.slice(off..end) else {
+                return Err(Error::OutOfBounds);
+            };
+            Ok(f(&mut window))
+        }) else {
+            return Err(Error::Busy);
         };
-        ipcbuf.rd8(off)
+        ret
     }
 
-    fn wr8(&mut self, off: usize, value: u8) {
-        let Some(mut inner) = self.rt.ipcbufbuf.take() else {
-            return;
-        };
-        let Some(ipcbuf) = inner.iter_mut().nth(self.ipcbuf) else {
-            return;
-        };
-        ipcbuf.wr8(off, value);
+    // the buffer is claimed before the queue sees it so no access slips in
+    // once the device owns it, pending while another one is in flight
+    fn submit(&mut self) -> Poll<Result<(), Q::Error>> {
+        let idx = self.ipcbuf;
+        if !self.rt.with_ipcbufbuf(self.hart, |bufs| bufs.claim(idx)).unwrap_or(false) {
+            return Poll::Pending;
+        }
+        let poll = self.rt.with_queue(self.hart, |queue| queue.push(idx)).unwrap_or(Poll::Pending);
+        if !matches!(poll, Poll::Ready(Ok(()))) {
+            self.rt.with_ipcbufbuf(self.hart, |bufs| bufs.release());
+        }
+        poll
+    }
+
+    // an error says nothing about the buffer, it stays with the device
+    fn poll(&mut self) -> Poll<Result<Q::Response, Q::Error>> {
+        let poll = self.rt.with_queue(self.hart, |queue| queue.pop()).unwrap_or(Poll::Pending);
+        if let Poll::Ready(Ok(_)) = poll {
+            self.rt.with_ipcbufbuf(self.hart, |bufs| bufs.release());
+        }
+        poll
+    }
+
+    #[cfg(feature = "alloc")]
+    fn heap_stats(&self) -> Option<HeapStats> {
+        self.rt.heap(self.hart).map(Heap::stats)
     }
 }
 
@@ -102,88 +388,86 @@ where T: Time {
  */
 pub struct RuntimeMain<'a, T, Q, const BUFNR: usize, const CHL: usize, const CHNR: usize> {
     timer: Cell<Option<T>>,
-    queue: Cell<Q>,
+    queue: RefCell<Q>,
     logbufbuf: RefCell<LogBufBuf<CHL, CHNR>>,
-    ipcbufbuf: Cell<Option<Deque<IPCByteBuf<'a>, BUFNR>>>,
+    ipcbufbuf: RefCell<IPCBufBuf<'a, BUFNR>>,
+    #[cfg(feature = "alloc")]
+    heap: Cell<Option<&'a Heap>>,
 }
 
 impl<'a, T, Q, const BUFNR: usize, const CHL: usize, const CHNR: usize>
-RuntimeMain<'a, T, Q, BUFNR, CHL, CHNR> {
-    pub fn new<B: FnMut(usize) -> IPCByteBuf<'a>>(timer: T, queue: Q, mut bufctr: B) -> Self {
-        Self {
-            timer: Cell::new(Some(timer)), queue: Cell::new(queue),
-            ipcbufbuf: Cell::new(Some(Deque::new(|idx| bufctr(idx)))),
-            logbufbuf: RefCell::new(LogBufBuf::default()),
-        }
+State<'a, T, Q, BUFNR, CHL, CHNR> for RuntimeMain<'a, T, Q, BUFNR, CHL, CHNR> {
+    fn with_timer<R, F: FnOnce(&mut T) -> R>(&self, _hart: usize, f: F) -> Option<R> {
+        let mut timer = self.timer.take()?;
+        let ret = f(&mut timer);
+        self.timer.set(Some(timer));
+        Some(ret)
     }
-}
 
-impl<'a, T, Q, const BUFNR: usize, const CHL: usize, const CHNR: usize>
-RuntimeMain<'a, T, Q, BUFNR, CHL, CHNR>
-where T: Time {
-    pub fn as_ref(&'a self) -> impl Runtime {
-        RuntimeRef {
-            rt: self, logbuf: 0, ipcbuf: 0,
-        }
+    fn with_queue<R, F: FnOnce(&mut Q) -> R>(&self, _hart: usize, f: F) -> Option<R> {
+        Some(f(&mut *self.queue.try_borrow_mut().ok()?))
     }
-}
 
-/*
- * log buffer buffer
- */
-// #[derive(Clone, Copy)]
-struct LogBufBuf<const L: usize, const NR: usize> {
-    deque: Deque<LogBuf<L>, NR>,
+    fn with_logbufbuf<R, F: FnOnce(&mut LogBufBuf<CHL, CHNR>) -> R>(&self, _hart: usize, f: F) -> Option<R> {
+        Some(f(&mut *self.logbufbuf.try_borrow_mut().ok()?))
+    }
+
+    fn with_ipcbufbuf<R, F: FnOnce(&mut IPCBufBuf<'a, BUFNR>) -> R>(&self, _hart: usize, f: F) -> Option<R> {
+        Some(f(&mut *self.ipcbufbuf.try_borrow_mut().ok()?))
+    }
+
+    #[cfg(feature = "alloc")]
+    fn heap(&self, _hart: usize) -> Option<&'a Heap> {
+        self.heap.get()
+    }
 }
 
-impl<const L: usize, const NR: usize>
-Default for LogBufBuf<L, NR> {
-    fn default() -> Self {
+impl<'a, T, Q, const BUFNR: usize, const CHL: usize, const CHNR: usize>
+RuntimeMain<'a, T, Q, BUFNR, CHL, CHNR> {
+    pub fn new<B: FnMut(usize) -> IPCByteBuf<'a>>(timer: T, queue: Q, bufctr: B) -> Self {
         Self {
-            deque: Deque::default(),
+            timer: Cell::new(Some(timer)), queue: RefCell::new(queue),
+            ipcbufbuf: RefCell::new(IPCBufBuf::new(bufctr)),
+            logbufbuf: RefCell::new(LogBufBuf::default()),
+            #[cfg(feature = "alloc")]
+            heap: Cell::new(None),
         }
     }
-}
 
-impl<const L: usize, const NR: usize>
-LogBufBuf<L, NR> {
-    fn iter(&self) -> DequeRefIter<'_, LogBuf<L>> {
-        self.deque.iter()
+    #[cfg(feature = "alloc")]
+    pub fn set_heap(&self, heap: &'a Heap) {
+        self.heap.set(Some(heap));
     }
 
-    fn iter_mut(&mut self) -> DequeMutRefIter<'_, LogBuf<L>> {
-        self.deque.iter_mut()
+    pub fn for_each_record<F: FnMut(&LogRecord)>(&self, chan: usize, f: F) -> Result<(), logsink::Error> {
+        let Ok(logbufbuf) = self.logbufbuf.try_borrow() else {
+            return Err(logsink::Error::Busy);
+        };
+        for_each_record(&logbufbuf, chan, f);
+        Ok(())
     }
-}
 
-/*
- * log buffer
- */
-#[derive(Clone, Copy)]
-struct LogBuf<const L: usize> {
-    data: Deque<u8, L>,
-}
+    // try_borrow_mut keeps this usable from a panic handler that interrupted
+    // a log call, the same goes for for_each_record
+    pub fn drain<S: LogSink>(&self, chan: usize, sink: S) -> Result<usize, logsink::Error> {
+        let Ok(mut logbufbuf) = self.logbufbuf.try_borrow_mut() else {
+            return Err(logsink::Error::Busy);
+        };
+        drain(&mut logbufbuf, chan, sink)
+    }
 
-impl<const L: usize>
-Default for LogBuf<L> {
-    fn default() -> Self {
-        Self {
-            data: Deque::default(),
-        }
+    pub fn drain_all<S: LogSink>(&self, sink: S) -> Result<usize, logsink::Error> {
+        let Ok(mut logbufbuf) = self.logbufbuf.try_borrow_mut() else {
+            return Err(logsink::Error::Busy);
+        };
+        drain_all(&mut logbufbuf, sink)
     }
 }
 
-impl<const L: usize>
-fmt::Write for LogBuf<L> {
-    fn write_str(&mut self, s: &str) -> Result<(), fmt::Error> {
-        if self.data.is_full() {
-            for _ in 0..s.len() {
-                self.data.pop();
-            }
-        }
-        for b in s.as_bytes() {
-            self.data.push(*b);
-        }
-        Ok(())
+impl<'a, T, Q, const BUFNR: usize, const CHL: usize, const CHNR: usize>
+RuntimeMain<'a, T, Q, BUFNR, CHL, CHNR>
+where T: Time, Q: Queue<Request = usize> {
+    pub fn as_ref(&'a self) -> impl Runtime<Response = Q::Response, QueueError = Q::Error> {
+        RuntimeRef::new(self, 0)
     }
 }
@@ -0,0 +1,420 @@
+use core::fmt::{ self, Write };
+use core::str;
+use crate::bytebuf::{ self, ByteBuf };
+use crate::bytebuf::stream::{ ByteReader, ByteWriter };
+use crate::elf::{ self, ElfParser };
+use crate::runtime::log::{ LogRecord, MSG_LEN };
+
+pub use toolkit_unsafe::intern;
+
+pub const SECTION: &str = "toolkit_fmt";
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Error {
+    UnknownFmt,
+    BadFmt,
+    BadArgs,
+    Fmt,
+}
+
+impl From<fmt::Error> for Error {
+    fn from(_: fmt::Error) -> Self {
+        Error::Fmt
+    }
+}
+
+impl From<bytebuf::Error> for Error {
+    fn from(_: bytebuf::Error) -> Self {
+        Error::BadArgs
+    }
+}
+
+/// Only implicit arguments with the specs the host decoder understands are
+/// accepted, anything else fails the build:
+///
+/// ```compile_fail
+/// fn swapped<R: toolkit::runtime::Runtime>(rt: &mut R) {
+///     toolkit::binlog!(rt, toolkit::runtime::log::Level::Info, "{1} {0}", 1u8, 2u8);
+/// }
+/// ```
+///
+/// ```compile_fail
+/// fn named<R: toolkit::runtime::Runtime>(rt: &mut R, irq: u8) {
+///     toolkit::binlog!(rt, toolkit::runtime::log::Level::Info, "{irq}");
+/// }
+/// ```
+///
+/// ```compile_fail
+/// fn aligned<R: toolkit::runtime::Runtime>(rt: &mut R) {
+///     toolkit::binlog!(rt, toolkit::runtime::log::Level::Info, "{:>8} {:.3}", 1u8, 2u8);
+/// }
+/// ```
+#[macro_export]
+macro_rules! binlog {
+    ($rt:expr, $level:expr, $fmt:literal $(, $arg:expr)* $(,)?) => {{
+        #[allow(unused_imports)]
+        use $crate::runtime::Runtime as _;
+        const _: () = assert!($crate::runtime::binlog::check_fmt($fmt), "unsupported binlog! format");
+        // type check the arguments against the format string, never run
+        if false {
+            let _ = format_args!($fmt $(, $arg)*);
+        }
+        let level = $level;
+        if $rt.enabled(level) {
+            let mut enc = $crate::runtime::binlog::Encoder::new(
+                $crate::runtime::binlog::intern!($fmt));
+            $( enc.arg(&$arg); )*
+            $rt.log_binary(level, enc.as_bytes());
+        }
+    }};
+}
+
+/*
+ * the subset of format strings the decoder understands: implicit arguments
+ * only, with specs of the form [#][0][width][x|X|b|o|?]
+ */
+pub const fn check_fmt(fmt: &str) -> bool {
+    let fmt = fmt.as_bytes();
+    let mut idx = 0;
+    while idx < fmt.len() {
+        let escaped = idx + 1 < fmt.len() && fmt[idx + 1] == fmt[idx];
+        match fmt[idx] {
+            b'{' | b'}' if escaped => idx += 2,
+            b'{' => {
+                let mut end = idx + 1;
+                while end < fmt.len() && fmt[end] != b'}' {
+                    end += 1;
+                }
+                if end == fmt.len() {
+                    return false;
+                }
+                let spec = fmt.split_at(end).0.split_at(idx + 1).1;
+                if !check_spec(spec) {
+                    return false;
+                }
+                idx = end + 1;
+            },
+            b'}' => return false,
+            _ => idx += 1,
+        }
+    }
+    true
+}
+
+const fn check_spec(spec: &[u8]) -> bool {
+    if spec.is_empty() {
+        return true;
+    }
+    // positional and named arguments would be decoded out of order
+    if spec[0] != b':' {
+        return false;
+    }
+    let mut idx = 1;
+    if idx < spec.len() && spec[idx] == b'#' {
+        idx += 1;
+    }
+    while idx < spec.len() && spec[idx].is_ascii_digit() {
+        idx += 1;
+    }
+    match spec.len() - idx {
+        0 => true,
+        1 => matches!(spec[idx], b'x' | b'X' | b'b' | b'o' | b'?'),
+        _ => false,
+    }
+}
+
+/*
+ * arguments, each prefixed by a type tag
+ */
+const ARG_UINT: u8 = 0;
+const ARG_INT: u8 = 1;
+const ARG_BOOL: u8 = 2;
+const ARG_CHAR: u8 = 3;
+const ARG_STR: u8 = 4;
+
+pub trait LogArg {
+    fn encode<B: ByteBuf>(&self, writer: &mut ByteWriter<B>) -> Result<(), bytebuf::Error>;
+}
+
+macro_rules! uint_arg {
+    ($($ty:ty),*) => {
+        $(
+            impl LogArg for $ty {
+                fn encode<B: ByteBuf>(&self, writer: &mut ByteWriter<B>) -> Result<(), bytebuf::Error> {
+                    writer.write_u8(ARG_UINT)?;
+                    writer.write_varint(*self as u64)
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! int_arg {
+    ($($ty:ty),*) => {
+        $(
+            impl LogArg for $ty {
+                fn encode<B: ByteBuf>(&self, writer: &mut ByteWriter<B>) -> Result<(), bytebuf::Error> {
+                    let value = *self as i64;
+                    writer.write_u8(ARG_INT)?;
+                    writer.write_varint(((value << 1) ^ (value >> 63)) as u64)
+                }
+            }
+        )*
+    };
+}
+
+uint_arg!(u8, u16, u32, u64, usize);
+int_arg!(i8, i16, i32, i64, isize);
+
+impl LogArg for bool {
+    fn encode<B: ByteBuf>(&self, writer: &mut ByteWriter<B>) -> Result<(), bytebuf::Error> {
+        writer.write_u8(ARG_BOOL)?;
+        writer.write_u8(u8::from(*self))
+    }
+}
+
+impl LogArg for char {
+    fn encode<B: ByteBuf>(&self, writer: &mut ByteWriter<B>) -> Result<(), bytebuf::Error> {
+        writer.write_u8(ARG_CHAR)?;
+        writer.write_varint(u64::from(u32::from(*self)))
+    }
+}
+
+impl LogArg for str {
+    fn encode<B: ByteBuf>(&self, writer: &mut ByteWriter<B>) -> Result<(), bytebuf::Error> {
+        writer.write_u8(ARG_STR)?;
+        writer.write_varint(self.len() as u64)?;
+        writer.write_bytes(self.as_bytes())
+    }
+}
+
+impl<T> LogArg for &T
+where T: LogArg + ?Sized {
+    fn encode<B: ByteBuf>(&self, writer: &mut ByteWriter<B>) -> Result<(), bytebuf::Error> {
+        (**self).encode(writer)
+    }
+}
+
+/*
+ * target side encoder
+ */
+pub struct Encoder {
+    writer: ByteWriter<[u8; MSG_LEN]>,
+    truncated: bool,
+}
+
+impl Encoder {
+    pub fn new(id: usize) -> Self {
+        let mut writer = ByteWriter::new([0; MSG_LEN]);
+        let truncated = writer.write_varint(id as u64).is_err();
+        Self {
+            writer, truncated,
+        }
+    }
+
+    // arguments that do not fit are dropped whole
+    pub fn arg<A: LogArg + ?Sized>(&mut self, arg: &A) {
+        let pos = self.writer.pos();
+        if self.truncated || arg.encode(&mut self.writer).is_err() {
+            let _ = self.writer.seek(pos);
+            self.truncated = true;
+        }
+    }
+
+    pub fn is_truncated(&self) -> bool {
+        self.truncated
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.writer.get_ref()[..self.writer.pos()]
+    }
+}
+
+/*
+ * host side format string tables
+ */
+pub trait FmtTable {
+    fn lookup(&self, id: usize) -> Option<&str>;
+}
+
+pub struct SectionFmtTable<'a> {
+    data: &'a [u8],
+}
+
+struct Discard;
+
+impl fmt::Write for Discard {
+    fn write_str(&mut self, _: &str) -> Result<(), fmt::Error> {
+        Ok(())
+    }
+}
+
+impl<'a> SectionFmtTable<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        Self {
+            data,
+        }
+    }
+
+    pub fn from_elf(elf: &'a [u8]) -> Result<Self, elf::Error> {
+        let mut parser = ElfParser::new(Discard);
+        parser.pull(elf)?;
+        parser.find_section(elf, SECTION).map(Self::new)
+    }
+}
+
+impl<'a>
+FmtTable for SectionFmtTable<'a> {
+    fn lookup(&self, id: usize) -> Option<&str> {
+        let raw = self.data.get(id..)?;
+        let end = raw.iter().position(|byte| *byte == 0)?;
+        str::from_utf8(&raw[..end]).ok()
+    }
+}
+
+/*
+ * host side decoder
+ */
+pub fn decode<T, W>(table: &T, rec: &LogRecord, out: &mut W) -> Result<(), Error>
+where T: FmtTable + ?Sized, W: fmt::Write {
+    if !rec.binary {
+        return Ok(out.write_str(rec.msg())?);
+    }
+
+    let payload = rec.as_bytes();
+    let mut buf = [0; MSG_LEN];
+    buf[..payload.len()].copy_from_slice(payload);
+    let mut reader = ByteReader::new(&mut buf[..payload.len()]);
+
+    let id = usize::try_from(reader.read_varint()?).map_err(|_| Error::UnknownFmt)?;
+    let Some(mut fmt) = table.lookup(id) else {
+        return Err(Error::UnknownFmt);
+    };
+
+    while let Some(idx) = fmt.find(['{', '}']) {
+        out.write_str(&fmt[..idx])?;
+        let tail = &fmt[idx..];
+        if tail.starts_with("{{") || tail.starts_with("}}") {
+            out.write_str(&tail[..1])?;
+            fmt = &tail[2..];
+            continue;
+        }
+        let end = match tail.find('}') {
+            Some(end) if tail.starts_with('{') => end,
+            _ => return Err(Error::BadFmt),
+        };
+        let spec = &tail[1..end];
+        if !check_spec(spec.as_bytes()) {
+            return Err(Error::BadFmt);
+        }
+        decode_arg(&mut reader, spec.strip_prefix(':').unwrap_or(spec), out)?;
+        fmt = &tail[end + 1..];
+    }
+    Ok(out.write_str(fmt)?)
+}
+
+fn decode_arg<B, W>(reader: &mut ByteReader<B>, spec: &str, out: &mut W) -> Result<(), Error>
+where B: ByteBuf, W: fmt::Write {
+    // truncated record
+    let Ok(tag) = reader.read_u8() else {
+        return Ok(out.write_str("{?}")?);
+    };
+    let spec = Spec::parse(spec);
+    match tag {
+        ARG_UINT => spec.write_int(out, reader.read_varint()?),
+        ARG_INT => {
+            let value = reader.read_varint()?;
+            spec.write_int(out, ((value >> 1) as i64) ^ -((value & 1) as i64))
+        },
+        ARG_BOOL => spec.write(out, &(reader.read_u8()? != 0)),
+        ARG_CHAR => {
+            let value = u32::try_from(reader.read_varint()?).map_err(|_| Error::BadArgs)?;
+            spec.write(out, &char::from_u32(value).ok_or(Error::BadArgs)?)
+        },
+        ARG_STR => {
+            let len = usize::try_from(reader.read_varint()?).map_err(|_| Error::BadArgs)?;
+            let mut buf = [0; MSG_LEN];
+            let buf = buf.get_mut(..len).ok_or(Error::BadArgs)?;
+            reader.read_bytes(buf)?;
+            spec.write(out, str::from_utf8(buf).map_err(|_| Error::BadArgs)?)
+        },
+        _ => Err(Error::BadArgs),
+    }
+}
+
+/*
+ * a spec accepted by check_spec
+ */
+struct Spec<'a> {
+    alt: bool,
+    zero: bool,
+    width: usize,
+    kind: &'a str,
+}
+
+struct Scratch {
+    buf: [u8; 72],
+    len: usize,
+}
+
+impl fmt::Write for Scratch {
+    fn write_str(&mut self, s: &str) -> Result<(), fmt::Error> {
+        let buf = self.buf.get_mut(self.len..self.len + s.len()).ok_or(fmt::Error)?;
+        buf.copy_from_slice(s.as_bytes());
+        self.len += s.len();
+        Ok(())
+    }
+}
+
+impl<'a> Spec<'a> {
+    fn parse(spec: &'a str) -> Self {
+        let (alt, spec) = match spec.strip_prefix('#') {
+            Some(spec) => (true, spec),
+            None => (false, spec),
+        };
+        let digits = spec.find(|c: char| !c.is_ascii_digit()).unwrap_or(spec.len());
+        Self {
+            alt,
+            zero: spec.starts_with('0'),
+            width: spec[..digits].parse().unwrap_or_default(),
+            kind: &spec[digits..],
+        }
+    }
+
+    fn write<W, T>(&self, out: &mut W, value: &T) -> Result<(), Error>
+    where W: fmt::Write, T: fmt::Display + fmt::Debug + ?Sized {
+        match self.kind {
+            "?" => write!(out, "{:w$?}", value, w = self.width)?,
+            _ => write!(out, "{:w$}", value, w = self.width)?,
+        }
+        Ok(())
+    }
+
+    fn write_int<W, T>(&self, out: &mut W, value: T) -> Result<(), Error>
+    where W: fmt::Write,
+          T: fmt::Display + fmt::LowerHex + fmt::UpperHex + fmt::Binary + fmt::Octal {
+        let mut scratch = Scratch { buf: [0; 72], len: 0 };
+        match (self.kind, self.alt) {
+            ("x", false) => write!(scratch, "{:x}", value)?,
+            ("x", true) => write!(scratch, "{:#x}", value)?,
+            ("X", false) => write!(scratch, "{:X}", value)?,
+            ("X", true) => write!(scratch, "{:#X}", value)?,
+            ("b", false) => write!(scratch, "{:b}", value)?,
+            ("b", true) => write!(scratch, "{:#b}", value)?,
+            ("o", false) => write!(scratch, "{:o}", value)?,
+            ("o", true) => write!(scratch, "{:#o}", value)?,
+            _ => write!(scratch, "{}", value)?,
+        }
+        let digits = str::from_utf8(&scratch.buf[..scratch.len]).map_err(|_| Error::Fmt)?;
+        let pad = self.width.saturating_sub(digits.len());
+        if !self.zero {
+            return Ok(write!(out, "{:pad$}{}", "", digits)?);
+        }
+        // zeros go between sign or radix prefix and the digits
+        let prefix = match self.kind {
+            "x" | "X" | "b" | "o" if self.alt => digits.find(['x', 'b', 'o']).map_or(0, |idx| idx + 1),
+            _ => digits.find(|c: char| c.is_ascii_digit()).unwrap_or(0),
+        };
+        Ok(write!(out, "{}{:0>pad$}{}", &digits[..prefix], "", &digits[prefix..])?)
+    }
+}
@@ -0,0 +1,223 @@
+use core::time::{ Duration };
+use crate::cmd::{ Poll };
+use crate::runtime::{ Time };
+
+pub mod future;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Error {
+    Full,
+    NoTask,
+}
+
+// Ready(Yield) made progress and wants to run again, Ready(Done) retires
+// the task, Pending means there was nothing to do
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Step {
+    Yield,
+    Done,
+}
+
+pub trait Task {
+    fn poll(&mut self) -> Poll<Step>;
+}
+
+impl<F> Task for F
+where F: FnMut() -> Poll<Step> {
+    fn poll(&mut self) -> Poll<Step> {
+        self()
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Policy {
+    #[default]
+    RoundRobin,
+    Priority,
+}
+
+// the slot generation tells a retired task from the one that reused its slot
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TaskId {
+    idx: usize,
+    generation: usize,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct TaskStats {
+    pub polls: usize,
+    pub busy: Duration,
+    pub max: Duration,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ExecStats {
+    pub passes: usize,
+    pub idles: usize,
+    pub idle: Duration,
+}
+
+struct Slot<'a> {
+    task: &'a mut dyn Task,
+    prio: u8,
+    stats: TaskStats,
+}
+
+/*
+ * fixed capacity cooperative executor
+ */
+pub struct Executor<'a, T, I, const NR: usize> {
+    slots: [Option<Slot<'a>>; NR],
+    generations: [usize; NR],
+    policy: Policy,
+    next: usize,
+    time: T,
+    idle: I,
+    stats: ExecStats,
+}
+
+impl<'a, T, I, const NR: usize>
+Executor<'a, T, I, NR>
+where T: Time, I: FnMut() {
+    pub fn new(time: T, idle: I) -> Self {
+        Self {
+            slots: core::array::from_fn(|_| None),
+            generations: [0; NR],
+            policy: Policy::default(), next: 0,
+            time, idle,
+            stats: ExecStats::default(),
+        }
+    }
+
+    pub fn policy(&self) -> Policy {
+        self.policy
+    }
+
+    pub fn set_policy(&mut self, policy: Policy) {
+        self.policy = policy;
+    }
+
+    pub fn spawn(&mut self, task: &'a mut dyn Task, prio: u8) -> Result<TaskId, Error> {
+        let Some(idx) = self.slots.iter().position(Option::is_none) else {
+            return Err(Error::Full);
+        };
+        self.slots[idx] = Some(Slot {
+            task, prio, stats: TaskStats::default(),
+        });
+        Ok(TaskId {
+            idx, generation: self.generations[idx],
+        })
+    }
+
+    fn slot(&self, id: TaskId) -> Option<&Slot<'a>> {
+        match self.generations.get(id.idx) {
+            Some(generation) if *generation == id.generation => self.slots[id.idx].as_ref(),
+            _ => None,
+        }
+    }
+
+    fn retire(&mut self, idx: usize) {
+        self.slots[idx] = None;
+        self.generations[idx] = self.generations[idx].wrapping_add(1);
+    }
+
+    pub fn cancel(&mut self, id: TaskId) -> Result<(), Error> {
+        if self.slot(id).is_none() {
+            return Err(Error::NoTask);
+        }
+        self.retire(id.idx);
+        Ok(())
+    }
+
+    pub fn is_running(&self, id: TaskId) -> bool {
+        self.slot(id).is_some()
+    }
+
+    pub fn len(&self) -> usize {
+        self.slots.iter().filter(|slot| slot.is_some()).count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn task_stats(&self, id: TaskId) -> Option<TaskStats> {
+        self.slot(id).map(|slot| slot.stats)
+    }
+
+    pub fn stats(&self) -> ExecStats {
+        self.stats
+    }
+
+    fn poll_slot(&mut self, idx: usize) -> Poll<Step> {
+        let Some(slot) = self.slots.get_mut(idx).and_then(Option::as_mut) else {
+            return Poll::Pending;
+        };
+        let start = self.time.time();
+        let poll = slot.task.poll();
+        let took = self.time.time().saturating_sub(start);
+
+        slot.stats.polls += 1;
+        slot.stats.busy += took;
+        slot.stats.max = slot.stats.max.max(took);
+        if let Poll::Ready(Step::Done) = poll {
+            self.retire(idx);
+        }
+        poll
+    }
+
+    // highest priority first, spawn order among equals
+    fn by_priority(&self) -> [usize; NR] {
+        let mut order: [usize; NR] = core::array::from_fn(|idx| idx);
+        let prio = |idx: usize| self.slots[idx].as_ref().map_or(0, |slot| slot.prio);
+        for end in 1..NR {
+            let mut idx = end;
+            while idx > 0 && prio(order[idx - 1]) < prio(order[idx]) {
+                order.swap(idx - 1, idx);
+                idx -= 1;
+            }
+        }
+        order
+    }
+
+    // one scheduling pass, false if every task was pending
+    pub fn run_once(&mut self) -> bool {
+        self.stats.passes += 1;
+        let mut progress = false;
+        match self.policy {
+            Policy::RoundRobin => {
+                let start = self.next;
+                self.next = (self.next + 1) % NR.max(1);
+                for off in 0..NR {
+                    let idx = (start + off) % NR;
+                    progress |= matches!(self.poll_slot(idx), Poll::Ready(_));
+                }
+            },
+            // lower priorities only run while everything above is pending
+            Policy::Priority => {
+                for idx in self.by_priority() {
+                    if let Poll::Ready(_) = self.poll_slot(idx) {
+                        progress = true;
+                        break;
+                    }
+                }
+            },
+        }
+        progress
+    }
+
+    pub fn idle(&mut self) {
+        let start = self.time.time();
+        (self.idle)();
+        self.stats.idles += 1;
+        self.stats.idle += self.time.time().saturating_sub(start);
+    }
+
+    pub fn run(&mut self) {
+        while !self.is_empty() {
+            if !self.run_once() {
+                self.idle();
+            }
+        }
+    }
+}
@@ -0,0 +1,144 @@
+use core::future::{ Future };
+use core::pin::{ Pin };
+use core::sync::atomic::{ AtomicBool, Ordering };
+use core::task::{ self, Context };
+use crate::runtime::{ Time };
+use crate::runtime::exec::{ Error, ExecStats, TaskId, TaskStats };
+use toolkit_unsafe::waker::{ flag_waker };
+
+type Slot<'a> = Option<Pin<&'a mut dyn Future<Output = ()>>>;
+
+/*
+ * allocation free future executor, tasks are pinned by the caller and
+ * woken through one 'static ready flag per slot
+ */
+pub struct Executor<'a, T, I, const NR: usize> {
+    slots: [Slot<'a>; NR],
+    stats: [TaskStats; NR],
+    generations: [usize; NR],
+    ready: &'static [AtomicBool; NR],
+    time: T,
+    idle: I,
+    exec: ExecStats,
+}
+
+impl<'a, T, I, const NR: usize>
+Executor<'a, T, I, NR>
+where T: Time, I: FnMut() {
+    pub fn new(ready: &'static [AtomicBool; NR], time: T, idle: I) -> Self {
+        Self {
+            slots: core::array::from_fn(|_| None),
+            stats: [TaskStats::default(); NR],
+            generations: [0; NR],
+            ready, time, idle,
+            exec: ExecStats::default(),
+        }
+    }
+
+    pub fn spawn(&mut self, fut: Pin<&'a mut dyn Future<Output = ()>>) -> Result<TaskId, Error> {
+        let Some(idx) = self.slots.iter().position(Option::is_none) else {
+            return Err(Error::Full);
+        };
+        self.slots[idx] = Some(fut);
+        self.stats[idx] = TaskStats::default();
+        self.ready[idx].store(true, Ordering::Release);
+        Ok(TaskId {
+            idx, generation: self.generations[idx],
+        })
+    }
+
+    fn retire(&mut self, idx: usize) {
+        self.slots[idx] = None;
+        self.generations[idx] = self.generations[idx].wrapping_add(1);
+    }
+
+    pub fn cancel(&mut self, id: TaskId) -> Result<(), Error> {
+        if !self.is_running(id) {
+            return Err(Error::NoTask);
+        }
+        self.retire(id.idx);
+        Ok(())
+    }
+
+    pub fn is_running(&self, id: TaskId) -> bool {
+        self.generations.get(id.idx) == Some(&id.generation) && self.slots[id.idx].is_some()
+    }
+
+    pub fn len(&self) -> usize {
+        self.slots.iter().filter(|slot| slot.is_some()).count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn task_stats(&self, id: TaskId) -> Option<TaskStats> {
+        self.is_running(id).then(|| self.stats[id.idx])
+    }
+
+    pub fn stats(&self) -> ExecStats {
+        self.exec
+    }
+
+    fn is_ready(&self) -> bool {
+        self.slots.iter().zip(self.ready.iter())
+            .any(|(slot, ready)| slot.is_some() && ready.load(Ordering::Acquire))
+    }
+
+    // polls every woken task once, false if none was woken
+    pub fn run_once(&mut self) -> bool {
+        self.exec.passes += 1;
+        let mut progress = false;
+        for idx in 0..NR {
+            let Some(fut) = self.slots[idx].as_mut() else {
+                continue;
+            };
+            if !self.ready[idx].swap(false, Ordering::AcqRel) {
+                continue;
+            }
+            let waker = flag_waker(&self.ready[idx]);
+            let mut cx = Context::from_waker(&waker);
+
+            let start = self.time.time();
+            let poll = fut.as_mut().poll(&mut cx);
+            let took = self.time.time().saturating_sub(start);
+
+            let stats = &mut self.stats[idx];
+            stats.polls += 1;
+            stats.busy += took;
+            stats.max = stats.max.max(took);
+            if let task::Poll::Ready(()) = poll {
+                self.retire(idx);
+            }
+            progress = true;
+        }
+        progress
+    }
+
+    pub fn idle(&mut self) {
+        let start = self.time.time();
+        (self.idle)();
+        self.exec.idles += 1;
+        self.exec.idle += self.time.time().saturating_sub(start);
+    }
+
+    // queues and timers cannot wake their tasks, whatever ended the idle
+    // may have completed them
+    fn wake_all(&self) {
+        for (slot, ready) in self.slots.iter().zip(self.ready.iter()) {
+            if slot.is_some() {
+                ready.store(true, Ordering::Release);
+            }
+        }
+    }
+
+    pub fn run(&mut self) {
+        while !self.is_empty() {
+            // a wake may have come in while the last pass was running
+            if !self.run_once() && !self.is_ready() {
+                self.idle();
+                self.wake_all();
+            }
+        }
+    }
+}
@@ -0,0 +1,310 @@
+use core::fmt;
+use core::str;
+use core::time::{ Duration };
+use crate::bytebuf::{ self, ByteBuf };
+use crate::bytebuf::stream::{ ByteReader };
+use crate::collection::deque::{ Deque, DequeRefIter, DequeMutRefIter };
+
+/*
+ * log levels
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+pub const DEFAULT_LEVEL: Level = Level::Info;
+
+impl Level {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Error => "ERROR",
+            Self::Warn => "WARN",
+            Self::Info => "INFO",
+            Self::Debug => "DEBUG",
+            Self::Trace => "TRACE",
+        }
+    }
+}
+
+impl fmt::Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(self.as_str())
+    }
+}
+
+impl From<Level> for u8 {
+    fn from(level: Level) -> u8 {
+        level as u8
+    }
+}
+
+impl TryFrom<u8> for Level {
+    type Error = u8;
+
+    fn try_from(value: u8) -> Result<Self, u8> {
+        match value {
+            0 => Ok(Self::Error),
+            1 => Ok(Self::Warn),
+            2 => Ok(Self::Info),
+            3 => Ok(Self::Debug),
+            4 => Ok(Self::Trace),
+            _ => Err(value),
+        }
+    }
+}
+
+#[macro_export]
+macro_rules! log {
+    ($rt:expr, $level:expr, $($arg:tt)+) => {{
+        #[allow(unused_imports)]
+        use $crate::runtime::Runtime as _;
+        $rt.log($level, format_args!($($arg)+))
+    }};
+}
+
+#[macro_export]
+macro_rules! error {
+    ($rt:expr, $($arg:tt)+) => { $crate::log!($rt, $crate::runtime::log::Level::Error, $($arg)+) };
+}
+
+#[macro_export]
+macro_rules! warn {
+    ($rt:expr, $($arg:tt)+) => { $crate::log!($rt, $crate::runtime::log::Level::Warn, $($arg)+) };
+}
+
+#[macro_export]
+macro_rules! info {
+    ($rt:expr, $($arg:tt)+) => { $crate::log!($rt, $crate::runtime::log::Level::Info, $($arg)+) };
+}
+
+#[macro_export]
+macro_rules! debug {
+    ($rt:expr, $($arg:tt)+) => { $crate::log!($rt, $crate::runtime::log::Level::Debug, $($arg)+) };
+}
+
+#[macro_export]
+macro_rules! trace {
+    ($rt:expr, $($arg:tt)+) => { $crate::log!($rt, $crate::runtime::log::Level::Trace, $($arg)+) };
+}
+
+/*
+ * log record
+ */
+pub const MSG_LEN: usize = 116;
+
+// len: u16, level: u8, chan: u8, time: u64 nanoseconds
+pub const HDR_LEN: usize = 12;
+const LEVEL_BINARY: u8 = 0x80;
+
+#[derive(Clone, Copy)]
+pub struct LogRecord {
+    pub time: Duration,
+    pub level: Level,
+    pub chan: usize,
+    // message holds a runtime::binlog payload instead of text
+    pub binary: bool,
+    len: usize,
+    msg: [u8; MSG_LEN],
+}
+
+impl LogRecord {
+    pub fn new(time: Duration, level: Level, chan: usize) -> Self {
+        Self {
+            time, level, chan, binary: false, len: 0, msg: [0; MSG_LEN],
+        }
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.msg[..self.len]
+    }
+
+    pub fn msg(&self) -> &str {
+        match str::from_utf8(self.as_bytes()) {
+            Ok(msg) => msg,
+            // message was truncated inside a multibyte character
+            Err(err) => str::from_utf8(&self.msg[..err.valid_up_to()]).unwrap_or_default(),
+        }
+    }
+
+    pub fn read_from<B: ByteBuf>(reader: &mut ByteReader<B>) -> Result<Self, bytebuf::Error> {
+        decode(&mut core::iter::from_fn(|| reader.read_u8().ok())).ok_or(bytebuf::Error::EndOfBuf)
+    }
+
+    pub(crate) fn header(&self, len: u16) -> [u8; HDR_LEN] {
+        let time = u64::try_from(self.time.as_nanos()).unwrap_or(u64::MAX);
+        let chan = u8::try_from(self.chan).unwrap_or(u8::MAX);
+        let mut hdr = [0; HDR_LEN];
+        hdr[0..2].copy_from_slice(&len.to_le_bytes());
+        hdr[2] = u8::from(self.level) | if self.binary { LEVEL_BINARY } else { 0 };
+        hdr[3] = chan;
+        hdr[4..].copy_from_slice(&time.to_le_bytes());
+        hdr
+    }
+
+    pub(crate) fn push_bytes(&mut self, bytes: &[u8]) {
+        let len = bytes.len().min(MSG_LEN - self.len);
+        self.msg[self.len..self.len + len].copy_from_slice(&bytes[..len]);
+        self.len += len;
+    }
+
+    pub(crate) fn trim_newline(&mut self) {
+        if self.as_bytes().last() == Some(&b'\n') {
+            self.len -= 1;
+        }
+    }
+}
+
+impl Default for LogRecord {
+    fn default() -> Self {
+        Self::new(Duration::default(), DEFAULT_LEVEL, 0)
+    }
+}
+
+impl fmt::Write for LogRecord {
+    fn write_str(&mut self, s: &str) -> Result<(), fmt::Error> {
+        self.push_bytes(s.as_bytes());
+        Ok(())
+    }
+}
+
+impl fmt::Debug for LogRecord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LogRecord")
+            .field("time", &self.time)
+            .field("level", &self.level)
+            .field("chan", &self.chan)
+            .field("binary", &self.binary)
+            .field("msg", &self.msg())
+            .finish()
+    }
+}
+
+impl fmt::Display for LogRecord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?} {:<5} [{}] ", self.time, self.level, self.chan)?;
+        match self.binary {
+            true => write!(f, "<{} bytes>", self.len),
+            false => f.write_str(self.msg()),
+        }
+    }
+}
+
+fn decode<I: Iterator<Item = u8>>(iter: &mut I) -> Option<LogRecord> {
+    let mut hdr = [0; HDR_LEN];
+    for byte in hdr.iter_mut() {
+        *byte = iter.next()?;
+    }
+    let [len0, len1, level, chan, time @ ..] = hdr;
+    let len = usize::from(u16::from_le_bytes([len0, len1]));
+    let mut rec = LogRecord::new(
+        Duration::from_nanos(u64::from_le_bytes(time)),
+        Level::try_from(level & !LEVEL_BINARY).unwrap_or(Level::Error),
+        usize::from(chan),
+    );
+    rec.binary = level & LEVEL_BINARY != 0;
+    for _ in 0..len {
+        let byte = iter.next()?;
+        if rec.len < MSG_LEN {
+            rec.msg[rec.len] = byte;
+            rec.len += 1;
+        }
+    }
+    Some(rec)
+}
+
+/*
+ * log buffer buffer
+ */
+pub(crate) struct LogBufBuf<const L: usize, const NR: usize> {
+    deque: Deque<LogBuf<L>, NR>,
+}
+
+impl<const L: usize, const NR: usize>
+Default for LogBufBuf<L, NR> {
+    fn default() -> Self {
+        Self {
+            deque: Deque::full(|_| LogBuf::default()),
+        }
+    }
+}
+
+impl<const L: usize, const NR: usize>
+LogBufBuf<L, NR> {
+    pub(crate) fn iter(&self) -> DequeRefIter<'_, LogBuf<L>> {
+        self.deque.iter()
+    }
+
+    pub(crate) fn iter_mut(&mut self) -> DequeMutRefIter<'_, LogBuf<L>> {
+        self.deque.iter_mut()
+    }
+}
+
+/*
+ * log buffer
+ */
+#[derive(Clone, Copy)]
+pub(crate) struct LogBuf<const L: usize> {
+    data: Deque<u8, L>,
+    pub(crate) level: Option<Level>,
+}
+
+impl<const L: usize>
+Default for LogBuf<L> {
+    fn default() -> Self {
+        Self {
+            data: Deque::default(),
+            level: Some(DEFAULT_LEVEL),
+        }
+    }
+}
+
+impl<const L: usize>
+LogBuf<L> {
+    pub(crate) fn push(&mut self, rec: &LogRecord) {
+        let Some(max) = L.checked_sub(HDR_LEN) else {
+            return;
+        };
+        let msg = &rec.as_bytes()[..rec.len.min(max).min(usize::from(u16::MAX))];
+        while self.data.free() < HDR_LEN + msg.len() {
+            if self.pop().is_none() {
+                return;
+            }
+        }
+        let hdr = rec.header(u16::try_from(msg.len()).unwrap_or_default());
+        for byte in hdr.into_iter().chain(msg.iter().copied()) {
+            self.data.push(byte);
+        }
+    }
+
+    pub(crate) fn pop(&mut self) -> Option<LogRecord> {
+        decode(&mut core::iter::from_fn(|| self.data.pop()))
+    }
+
+    pub(crate) fn peek(&self) -> Option<LogRecord> {
+        self.records().next()
+    }
+
+    pub(crate) fn records(&self) -> LogRecordIter<'_> {
+        LogRecordIter {
+            iter: self.data.iter(),
+        }
+    }
+}
+
+pub(crate) struct LogRecordIter<'a> {
+    iter: DequeRefIter<'a, u8>,
+}
+
+impl<'a>
+Iterator for LogRecordIter<'a> {
+    type Item = LogRecord;
+
+    fn next(&mut self) -> Option<LogRecord> {
+        decode(&mut self.iter.by_ref().copied())
+    }
+}
@@ -0,0 +1,178 @@
+use core::fmt::{ self, Write };
+use core::time::{ Duration };
+use crate::bytebuf::{ ByteBuf, VolatileByteBuf };
+use crate::bytebuf::stream::{ ByteWriter };
+use crate::collection::deque::{ Deque, DequeRefIter };
+use crate::runtime::{ Time };
+use crate::runtime::log::{ HDR_LEN, LogRecord };
+use crate::runtime::time::{ Deadline };
+use crate::uart::{ Uart, lsr };
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Error {
+    Busy,
+    Full,
+    Timeout,
+}
+
+pub trait LogSink {
+    fn write(&mut self, rec: &LogRecord) -> Result<(), Error>;
+}
+
+impl<S> LogSink for &mut S
+where S: LogSink + ?Sized {
+    fn write(&mut self, rec: &LogRecord) -> Result<(), Error> {
+        (**self).write(rec)
+    }
+}
+
+/*
+ * 16550 console
+ */
+pub struct UartSink<B, T> {
+    uart: Uart<B>,
+    time: T,
+    // budget for a whole record
+    timeout: Duration,
+    // bytes of the record identified by (time, chan) already on the wire
+    partial: Option<(Duration, usize)>,
+    sent: usize,
+}
+
+impl<B, T> UartSink<B, T>
+where B: VolatileByteBuf, T: Time {
+    pub fn new(io: B, time: T, timeout: Duration) -> Self {
+        Self {
+            uart: Uart::new(io),
+            time, timeout,
+            partial: None,
+            sent: 0,
+        }
+    }
+
+    pub fn into_inner(self) -> B {
+        self.uart.into_inner()
+    }
+
+    pub fn get_mut(&mut self) -> &mut B {
+        self.uart.get_mut()
+    }
+}
+
+// skips what an earlier, timed out attempt already sent
+struct UartWriter<'s, B, T> {
+    sink: &'s mut UartSink<B, T>,
+    deadline: Deadline,
+    pos: usize,
+}
+
+impl<B, T> fmt::Write for UartWriter<'_, B, T>
+where B: VolatileByteBuf, T: Time {
+    fn write_str(&mut self, s: &str) -> Result<(), fmt::Error> {
+        for byte in s.bytes() {
+            self.pos += 1;
+            if self.pos <= self.sink.sent {
+                continue;
+            }
+            let sink = &mut *self.sink;
+            let left = self.deadline.remaining(&mut sink.time);
+            sink.uart.lsr().wait_field(&mut sink.time, lsr::THRE, 1, left).map_err(|_| fmt::Error)?;
+            sink.uart.thr().write(byte);
+            sink.sent += 1;
+        }
+        Ok(())
+    }
+}
+
+impl<B, T> LogSink for UartSink<B, T>
+where B: VolatileByteBuf, T: Time {
+    fn write(&mut self, rec: &LogRecord) -> Result<(), Error> {
+        if self.partial != Some((rec.time, rec.chan)) {
+            self.partial = Some((rec.time, rec.chan));
+            self.sent = 0;
+        }
+        let deadline = Deadline::new(&mut self.time, self.timeout);
+        let mut out = UartWriter {
+            sink: self, deadline,
+            pos: 0,
+        };
+        write!(out, "{}\r\n", rec).map_err(|_| Error::Timeout)?;
+        self.partial = None;
+        Ok(())
+    }
+}
+
+/*
+ * shared memory export, read back with LogRecord::read_from
+ */
+pub struct MemSink<B> {
+    writer: ByteWriter<B>,
+}
+
+impl<B> MemSink<B>
+where B: ByteBuf {
+    pub fn new(buf: B) -> Self {
+        Self {
+            writer: ByteWriter::new(buf),
+        }
+    }
+
+    pub fn into_inner(self) -> B {
+        self.writer.into_inner()
+    }
+
+    pub fn pos(&self) -> usize {
+        self.writer.pos()
+    }
+}
+
+impl<B> LogSink for MemSink<B>
+where B: ByteBuf {
+    fn write(&mut self, rec: &LogRecord) -> Result<(), Error> {
+        let msg = rec.as_bytes();
+        let len = u16::try_from(msg.len()).map_err(|_| Error::Full)?;
+        if self.writer.remaining() < HDR_LEN + msg.len() {
+            return Err(Error::Full);
+        }
+        self.writer.write_bytes(&rec.header(len)).map_err(|_| Error::Full)?;
+        self.writer.write_bytes(msg).map_err(|_| Error::Full)
+    }
+}
+
+/*
+ * collector
+ */
+#[derive(Default)]
+pub struct CollectSink<const NR: usize> {
+    recs: Deque<LogRecord, NR>,
+}
+
+impl<const NR: usize>
+CollectSink<NR> {
+    pub fn len(&self) -> usize {
+        self.recs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.recs.is_empty()
+    }
+
+    pub fn iter(&self) -> DequeRefIter<'_, LogRecord> {
+        self.recs.iter()
+    }
+
+    pub fn pop(&mut self) -> Option<LogRecord> {
+        self.recs.pop()
+    }
+}
+
+impl<const NR: usize>
+LogSink for CollectSink<NR> {
+    fn write(&mut self, rec: &LogRecord) -> Result<(), Error> {
+        if self.recs.is_full() {
+            return Err(Error::Full);
+        }
+        self.recs.push(*rec);
+        Ok(())
+    }
+}
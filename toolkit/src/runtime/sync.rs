@@ -0,0 +1,95 @@
+use crate::cmd::{ Queue };
+use crate::runtime::{ self, IPCBufBuf, Runtime, RuntimeRef, State, Time };
+use crate::runtime::log::{ LogBufBuf, LogRecord };
+use crate::runtime::sink::{ self as logsink, LogSink };
+use toolkit_unsafe::{ IPCByteBuf };
+use toolkit_unsafe::spin::{ SpinLock };
+
+#[cfg(feature = "alloc")]
+use toolkit_unsafe::heap::{ Heap };
+
+/*
+ * runtime shared between harts, every piece of state sits behind its own
+ * spinlock so a hart logging never holds up one touching ipc buffers.
+ * locks spin on other harts and fail on the one holding them
+ */
+pub struct RuntimeSync<'a, T, Q, const BUFNR: usize, const CHL: usize, const CHNR: usize> {
+    timer: SpinLock<T>,
+    queue: SpinLock<Q>,
+    logbufbuf: SpinLock<LogBufBuf<CHL, CHNR>>,
+    ipcbufbuf: SpinLock<IPCBufBuf<'a, BUFNR>>,
+    #[cfg(feature = "alloc")]
+    heap: SpinLock<Option<&'a Heap>>,
+}
+
+impl<'a, T, Q, const BUFNR: usize, const CHL: usize, const CHNR: usize>
+State<'a, T, Q, BUFNR, CHL, CHNR> for RuntimeSync<'a, T, Q, BUFNR, CHL, CHNR> {
+    fn with_timer<R, F: FnOnce(&mut T) -> R>(&self, hart: usize, f: F) -> Option<R> {
+        self.timer.with_owner(hart, f)
+    }
+
+    fn with_queue<R, F: FnOnce(&mut Q) -> R>(&self, hart: usize, f: F) -> Option<R> {
+        self.queue.with_owner(hart, f)
+    }
+
+    fn with_logbufbuf<R, F: FnOnce(&mut LogBufBuf<CHL, CHNR>) -> R>(&self, hart: usize, f: F) -> Option<R> {
+        self.logbufbuf.with_owner(hart, f)
+    }
+
+    fn with_ipcbufbuf<R, F: FnOnce(&mut IPCBufBuf<'a, BUFNR>) -> R>(&self, hart: usize, f: F) -> Option<R> {
+        self.ipcbufbuf.with_owner(hart, f)
+    }
+
+    #[cfg(feature = "alloc")]
+    fn heap(&self, hart: usize) -> Option<&'a Heap> {
+        self.heap.with_owner(hart, |heap| *heap).flatten()
+    }
+}
+
+impl<'a, T, Q, const BUFNR: usize, const CHL: usize, const CHNR: usize>
+RuntimeSync<'a, T, Q, BUFNR, CHL, CHNR> {
+    pub fn new<B: FnMut(usize) -> IPCByteBuf<'a>>(timer: T, queue: Q, bufctr: B) -> Self {
+        Self {
+            timer: SpinLock::new(timer), queue: SpinLock::new(queue),
+            ipcbufbuf: SpinLock::new(IPCBufBuf::new(bufctr)),
+            logbufbuf: SpinLock::new(LogBufBuf::default()),
+            #[cfg(feature = "alloc")]
+            heap: SpinLock::new(None),
+        }
+    }
+
+    // every entry point names the calling hart, the locks tell re-entry on
+    // it apart from contention with the others
+    #[cfg(feature = "alloc")]
+    pub fn set_heap(&self, hart: usize, heap: &'a Heap) -> Result<(), runtime::Error> {
+        self.heap.with_owner(hart, |slot| *slot = Some(heap)).ok_or(runtime::Error::Busy)
+    }
+
+    pub fn for_each_record<F: FnMut(&LogRecord)>(&self, hart: usize, chan: usize, f: F) -> Result<(), logsink::Error> {
+        self.logbufbuf.with_owner(hart, |logbufbuf| runtime::for_each_record(logbufbuf, chan, f))
+            .ok_or(logsink::Error::Busy)
+    }
+
+    // Busy rather than spinning, the lock may be held by the very log call
+    // a panic handler interrupted
+    pub fn drain<S: LogSink>(&self, hart: usize, chan: usize, sink: S) -> Result<usize, logsink::Error> {
+        self.logbufbuf.try_with_owner(hart, |logbufbuf| runtime::drain(logbufbuf, chan, sink))
+            .unwrap_or(Err(logsink::Error::Busy))
+    }
+
+    pub fn drain_all<S: LogSink>(&self, hart: usize, sink: S) -> Result<usize, logsink::Error> {
+        self.logbufbuf.try_with_owner(hart, |logbufbuf| runtime::drain_all(logbufbuf, sink))
+            .unwrap_or(Err(logsink::Error::Busy))
+    }
+}
+
+impl<'a, T, Q, const BUFNR: usize, const CHL: usize, const CHNR: usize>
+RuntimeSync<'a, T, Q, BUFNR, CHL, CHNR>
+where T: Time, Q: Queue<Request = usize> {
+    // one handle per hart, each keeps its own channel and buffer selection.
+    // a handle taken on a hart already inside the runtime, say from a trap
+    // handler, sees the state it holds as Busy or drops its records
+    pub fn as_ref(&'a self, hart: usize) -> impl Runtime<Response = Q::Response, QueueError = Q::Error> {
+        RuntimeRef::new(self, hart)
+    }
+}
@@ -0,0 +1,781 @@
+extern crate std;
+
+use core::cell::{ Cell };
+use core::pin::{ pin };
+use core::sync::atomic::{ AtomicBool };
+use core::fmt::{ Write };
+use core::time::{ Duration };
+use crate::runtime::{ Error, Runtime, RuntimeMain, Time };
+use crate::bytebuf::stream::{ ByteReader };
+use crate::cmd::{ Poll, Queue };
+use crate::cmd::future::{ QueueExt, yield_now };
+use crate::runtime::exec::future::{ Executor as FutureExecutor };
+use crate::runtime::time::{ self, Deadline, Instant, Interval };
+use crate::runtime::exec::{ Error as ExecError, Executor, Policy, Step };
+use crate::runtime::binlog::{ self, Encoder, FmtTable, SectionFmtTable };
+use crate::runtime::log::{ HDR_LEN, Level, LogRecord };
+use crate::runtime::sync::{ RuntimeSync };
+use crate::runtime::sink::{ self, CollectSink, LogSink, MemSink, UartSink };
+use crate::sim::{ SimByteBuf };
+use crate::sim::uart::{ Uart16550 };
+use toolkit_unsafe::{ IPCByteBuf };
+
+#[derive(Default)]
+struct TestTime {
+    now: Duration,
+}
+
+impl Time for TestTime {
+    fn time(&mut self) -> Duration {
+        self.now += Duration::from_micros(1);
+        self.now
+    }
+}
+
+// no device behind it, every request is refused
+struct NoQueue;
+
+impl Queue for NoQueue {
+    type Request = usize;
+    type Response = ();
+    type Error = ();
+
+    fn push(&mut self, _req: usize) -> Poll<Result<(), ()>> {
+        Poll::Ready(Err(()))
+    }
+
+    fn pop(&mut self) -> Poll<Result<(), ()>> {
+        Poll::Ready(Err(()))
+    }
+}
+
+type TestRuntime<'a> = RuntimeMain<'a, TestTime, NoQueue, 1, 64, 2>;
+
+fn records(rt: &TestRuntime<'_>, chan: usize) -> std::vec::Vec<LogRecord> {
+    let mut recs = std::vec::Vec::new();
+    assert_eq!(rt.for_each_record(chan, |rec| recs.push(*rec)), Ok(()));
+    recs
+}
+
+fn records_sync<T, Q, const BUFNR: usize, const CHL: usize, const CHNR: usize>(
+    rt: &RuntimeSync<'_, T, Q, BUFNR, CHL, CHNR>, chan: usize,
+) -> std::vec::Vec<LogRecord> {
+    let mut recs = std::vec::Vec::new();
+    assert_eq!(rt.for_each_record(0, chan, |rec| recs.push(*rec)), Ok(()));
+    recs
+}
+
+fn msgs(rt: &TestRuntime<'_>, chan: usize) -> std::vec::Vec<std::string::String> {
+    records(rt, chan).iter().map(|rec| rec.msg().into()).collect()
+}
+
+#[repr(align(8))]
+struct Aligned([u8; 32]);
+
+#[test]
+fn log_levels() {
+    let main: TestRuntime<'_> = RuntimeMain::new(TestTime::default(), NoQueue, |_| IPCByteBuf::default());
+    let mut rt = main.as_ref();
+
+    crate::info!(rt, "up {}", 1);
+    crate::debug!(rt, "hidden");
+    assert_eq!(msgs(&main, 0), ["up 1"]);
+
+    rt.logbuf(1);
+    rt.set_level(1, Some(Level::Trace));
+    assert!(rt.enabled(Level::Trace));
+    crate::trace!(rt, "verbose");
+    crate::error!(rt, "bad");
+    assert_eq!(msgs(&main, 1), ["verbose", "bad"]);
+
+    rt.set_level(1, None);
+    assert_eq!(rt.level(), None);
+    crate::error!(rt, "silenced");
+    assert_eq!(rt.write_str("raw"), Ok(()));
+    assert_eq!(writeln!(rt, "raw {}", 1), Ok(()));
+    assert_eq!(msgs(&main, 1), ["verbose", "bad"]);
+
+    // raw writes are records at the default level
+    rt.set_level(1, Some(Level::Warn));
+    assert_eq!(writeln!(rt, "raw {}", 2), Ok(()));
+    crate::warn!(rt, "kept");
+    assert_eq!(msgs(&main, 1), ["verbose", "bad", "kept"]);
+}
+
+#[test]
+fn log_records() {
+    let main: TestRuntime<'_> = RuntimeMain::new(TestTime::default(), NoQueue, |_| IPCByteBuf::default());
+    let mut rt = main.as_ref();
+
+    rt.logbuf(1);
+    crate::warn!(rt, "first");
+    let _ = writeln!(rt, "raw {} {}", 2, 3);
+
+    let recs = records(&main, 1);
+    assert_eq!(recs.len(), 2);
+    assert_eq!(recs[0].time, Duration::from_micros(1));
+    assert_eq!(recs[0].level, Level::Warn);
+    assert_eq!(recs[0].chan, 1);
+    assert_eq!(recs[1].msg(), "raw 2 3");
+    assert_eq!(recs[1].level, Level::Info);
+
+    // 64 byte ring, 16 byte records: whole records are evicted
+    for idx in 0..5 {
+        crate::info!(rt, "msg{}", idx);
+    }
+    assert_eq!(msgs(&main, 1), ["msg1", "msg2", "msg3", "msg4"]);
+
+    // oversized messages are truncated to the ring
+    crate::info!(rt, "{:>60}", "x");
+    let recs = records(&main, 1);
+    assert_eq!(recs.len(), 1);
+    assert_eq!(recs[0].as_bytes().len(), 52);
+}
+
+#[test]
+fn log_drain() {
+    let main: TestRuntime<'_> = RuntimeMain::new(TestTime::default(), NoQueue, |_| IPCByteBuf::default());
+    let mut rt = main.as_ref();
+
+    crate::info!(rt, "a0");
+    rt.logbuf(1);
+    crate::info!(rt, "b0");
+    rt.logbuf(0);
+    crate::info!(rt, "a1");
+
+    // merged in timestamp order, stops when the sink fills up
+    let mut sink = CollectSink::<2>::default();
+    assert_eq!(main.drain_all(&mut sink), Err(sink::Error::Full));
+    let got: std::vec::Vec<(usize, std::string::String)> = sink.iter()
+        .map(|rec| (rec.chan, rec.msg().into())).collect();
+    assert_eq!(got, [(0, "a0".into()), (1, "b0".into())]);
+    assert_eq!(msgs(&main, 0), ["a1"]);
+
+    let mut sink = CollectSink::<2>::default();
+    assert_eq!(main.drain(0, &mut sink), Ok(1));
+    assert_eq!(main.drain(1, &mut sink), Ok(0));
+    assert_eq!(sink.pop().map(|rec| rec.time), Some(Duration::from_micros(3)));
+
+    // busy while a log call holds the channels
+    let guard = main.logbufbuf.borrow();
+    assert_eq!(main.drain_all(&mut sink), Err(sink::Error::Busy));
+    drop(guard);
+    let guard = main.logbufbuf.borrow_mut();
+    assert_eq!(main.for_each_record(0, |_| {}), Err(sink::Error::Busy));
+    drop(guard);
+}
+
+#[test]
+fn log_sinks() {
+    let main: TestRuntime<'_> = RuntimeMain::new(TestTime::default(), NoQueue, |_| IPCByteBuf::default());
+    let mut rt = main.as_ref();
+
+    crate::warn!(rt, "link {}", "down");
+    crate::info!(rt, "up");
+
+    let mut mem = MemSink::new([0u8; 64]);
+    assert_eq!(main.drain(0, &mut mem), Ok(2));
+    assert_eq!(mem.pos(), 2 * HDR_LEN + 9 + 2);
+
+    let mut reader = ByteReader::new(mem.into_inner());
+    let rec = LogRecord::read_from(&mut reader).unwrap();
+    assert_eq!((rec.level, rec.msg()), (Level::Warn, "link down"));
+    let rec = LogRecord::read_from(&mut reader).unwrap();
+    assert_eq!((rec.time, rec.msg()), (Duration::from_micros(2), "up"));
+
+    crate::error!(rt, "oops");
+    let mut uart = UartSink::new(
+        SimByteBuf::new(Uart16550::<64>::default()), TestTime::default(), Duration::from_micros(100),
+    );
+    assert_eq!(main.drain(0, &mut uart), Ok(1));
+    let mut out = std::vec::Vec::new();
+    let mut io = uart.into_inner();
+    while let Some(byte) = io.dev_mut().pop_tx() {
+        out.push(byte);
+    }
+    assert_eq!(out, b"3\xc2\xb5s ERROR [0] oops\r\n");
+}
+
+#[test]
+fn log_uart_resume() {
+    let main: TestRuntime<'_> = RuntimeMain::new(TestTime::default(), NoQueue, |_| IPCByteBuf::default());
+    let mut rt = main.as_ref();
+    crate::info!(rt, "resumed");
+
+    // the fifo is never drained while the sink waits, so every attempt
+    // stops after 8 bytes and the next one picks up from there
+    let mut uart = UartSink::new(
+        SimByteBuf::new(Uart16550::<8>::default()), TestTime::default(), Duration::from_micros(50),
+    );
+    let mut out = std::vec::Vec::new();
+    let mut tries = 0;
+    loop {
+        let res = main.drain(0, &mut uart);
+        while let Some(byte) = uart.get_mut().dev_mut().pop_tx() {
+            out.push(byte);
+        }
+        tries += 1;
+        match res {
+            Ok(nr) => {
+                assert_eq!(nr, 1);
+                break;
+            },
+            Err(err) => assert_eq!(err, sink::Error::Timeout),
+        }
+    }
+    assert_eq!(tries, 3);
+    assert_eq!(out, b"1\xc2\xb5s INFO  [0] resumed\r\n");
+    assert_eq!(main.drain(0, &mut uart), Ok(0));
+}
+
+#[test]
+fn log_binary() {
+    let main: TestRuntime<'_> = RuntimeMain::new(TestTime::default(), NoQueue, |_| IPCByteBuf::default());
+    let mut rt = main.as_ref();
+
+    crate::binlog!(rt, Level::Info, "irq {} status {:#06x} {{{:?}}} {:3}", 5u32, 0x1Fu16, "eth0", -3i32);
+    crate::binlog!(rt, Level::Debug, "hidden {}", 1u8);
+    crate::info!(rt, "text");
+
+    let recs = records(&main, 0);
+    assert_eq!(recs.len(), 2);
+    assert!(recs[0].binary);
+    assert_eq!(recs[0].level, Level::Info);
+    assert!(recs[0].as_bytes().len() < 16);
+    assert!(!recs[1].binary);
+
+    // format strings are resolved from the elf of this very test binary
+    let elf = std::env::current_exe().and_then(std::fs::read).unwrap();
+    let table = SectionFmtTable::from_elf(&elf).unwrap();
+    let mut out = std::string::String::new();
+    assert_eq!(binlog::decode(&table, &recs[0], &mut out), Ok(()));
+    assert_eq!(out, "irq 5 status 0x001f {\"eth0\"}  -3");
+
+    out.clear();
+    assert_eq!(binlog::decode(&table, &recs[1], &mut out), Ok(()));
+    assert_eq!(out, "text");
+
+    // arguments that do not fit are dropped, the decoder marks them
+    let mut enc = Encoder::new(crate::runtime::binlog::intern!("{} {}"));
+    enc.arg(&[0u8; 100][..].len());
+    enc.arg("x".repeat(120).as_str());
+    assert!(enc.is_truncated());
+    let mut rec = LogRecord::default();
+    rec.binary = true;
+    rec.push_bytes(enc.as_bytes());
+    out.clear();
+    assert_eq!(binlog::decode(&table, &rec, &mut out), Ok(()));
+    assert_eq!(out, "100 {?}");
+}
+
+struct OneFmt(&'static str);
+
+impl FmtTable for OneFmt {
+    fn lookup(&self, id: usize) -> Option<&str> {
+        (id == 0).then_some(self.0)
+    }
+}
+
+#[test]
+fn log_binary_fmt() {
+    assert!(binlog::check_fmt("{} {:#06x} {{{:?}}} {:3} {:}"));
+    assert!(!binlog::check_fmt("{1} {0}"));
+    assert!(!binlog::check_fmt("{irq}"));
+    assert!(!binlog::check_fmt("{:>8}"));
+    assert!(!binlog::check_fmt("{:.3}"));
+    assert!(!binlog::check_fmt("{:+}"));
+    assert!(!binlog::check_fmt("{"));
+    assert!(!binlog::check_fmt("}"));
+
+    let mut enc = Encoder::new(0);
+    enc.arg(&1u8);
+    enc.arg(&2u8);
+    let mut rec = LogRecord::default();
+    rec.binary = true;
+    rec.push_bytes(enc.as_bytes());
+
+    // tables not built by binlog! are checked when decoding
+    let mut out = std::string::String::new();
+    for fmt in ["{1} {0}", "{irq} {}", "{:>8} {}", "{:.3} {}"] {
+        assert_eq!(binlog::decode(&OneFmt(fmt), &rec, &mut out), Err(binlog::Error::BadFmt));
+    }
+    out.clear();
+    assert_eq!(binlog::decode(&OneFmt("{:5?} {:#04x}"), &rec, &mut out), Ok(()));
+    assert_eq!(out, "    1 0x02");
+}
+
+fn countdown<'a>(left: &'a Cell<usize>, polls: &'a Cell<usize>) -> impl FnMut() -> Poll<Step> + 'a {
+    move || {
+        polls.set(polls.get() + 1);
+        match left.get() {
+            0 => Poll::Ready(Step::Done),
+            n => {
+                left.set(n - 1);
+                Poll::Ready(Step::Yield)
+            },
+        }
+    }
+}
+
+#[test]
+fn exec_round_robin() {
+    let (left, apolls, bpolls) = (Cell::new(2), Cell::new(0), Cell::new(0));
+    let mut a = countdown(&left, &apolls);
+    let mut b = || {
+        bpolls.set(bpolls.get() + 1);
+        match bpolls.get() {
+            5 => Poll::Ready(Step::Done),
+            _ => Poll::Pending,
+        }
+    };
+    let idles = Cell::new(0);
+    let mut exec: Executor<'_, _, _, 4> = Executor::new(TestTime::default(), || idles.set(idles.get() + 1));
+
+    let (aid, bid) = (exec.spawn(&mut a, 0).unwrap(), exec.spawn(&mut b, 0).unwrap());
+    assert_eq!(exec.len(), 2);
+
+    exec.run_once();
+    let stats = exec.task_stats(aid).unwrap();
+    assert_eq!(stats.polls, 1);
+    assert_eq!(stats.busy, Duration::from_micros(1));
+
+    exec.run();
+    assert!(exec.is_empty());
+    assert!(!exec.is_running(aid) && !exec.is_running(bid));
+    assert_eq!(apolls.get(), 3);
+    assert_eq!(bpolls.get(), 5);
+    // only once a retired and b was left pending on its own
+    assert_eq!(idles.get(), 1);
+    assert_eq!(exec.stats().idles, 1);
+    assert_eq!(exec.stats().idle, Duration::from_micros(1));
+}
+
+#[test]
+fn exec_priority() {
+    let (left, hipolls, lopolls) = (Cell::new(2), Cell::new(0), Cell::new(0));
+    let mut hi = countdown(&left, &hipolls);
+    let mut lo = || {
+        lopolls.set(lopolls.get() + 1);
+        Poll::Pending
+    };
+    let mut exec: Executor<'_, _, _, 2> = Executor::new(TestTime::default(), || {});
+    exec.set_policy(Policy::Priority);
+
+    let lid = exec.spawn(&mut lo, 1).unwrap();
+    assert!(exec.spawn(&mut hi, 7).is_ok());
+    let mut full = || Poll::Pending;
+    assert_eq!(exec.spawn(&mut full, 0).err(), Some(ExecError::Full));
+
+    // lo only runs once hi has nothing left to do
+    assert!(exec.run_once());
+    assert!(exec.run_once());
+    assert_eq!(lopolls.get(), 0);
+    assert!(exec.run_once());
+    assert_eq!((hipolls.get(), lopolls.get()), (3, 0));
+    assert!(!exec.run_once());
+    assert_eq!(lopolls.get(), 1);
+
+    assert_eq!(exec.cancel(lid), Ok(()));
+    assert_eq!(exec.cancel(lid), Err(ExecError::NoTask));
+    assert!(exec.is_empty());
+
+    // a stale id does not reach the task that took over its slot
+    let mut next = || Poll::Pending;
+    let nid = exec.spawn(&mut next, 0).unwrap();
+    assert!(!exec.is_running(lid));
+    assert_eq!(exec.task_stats(lid), None);
+    assert_eq!(exec.cancel(lid), Err(ExecError::NoTask));
+    assert!(exec.is_running(nid));
+}
+
+// echoes requests doubled, every other call is pending like a slow device
+#[derive(Default)]
+struct EchoQueue {
+    calls: usize,
+    req: Option<u32>,
+}
+
+impl Queue for EchoQueue {
+    type Request = u32;
+    type Response = u32;
+    type Error = ();
+
+    fn push(&mut self, req: u32) -> Poll<Result<(), ()>> {
+        self.calls += 1;
+        match (self.calls % 2, self.req) {
+            (0, None) => {
+                self.req = Some(req);
+                Poll::Ready(Ok(()))
+            },
+            (_, Some(_)) => Poll::Ready(Err(())),
+            _ => Poll::Pending,
+        }
+    }
+
+    fn pop(&mut self) -> Poll<Result<u32, ()>> {
+        self.calls += 1;
+        match (self.calls % 2, self.req.take()) {
+            (0, Some(req)) => Poll::Ready(Ok(req * 2)),
+            (_, req) => {
+                self.req = req;
+                Poll::Pending
+            },
+        }
+    }
+}
+
+#[test]
+fn exec_future() {
+    static READY: [AtomicBool; 2] = [const { AtomicBool::new(false) }; 2];
+
+    let mut queue = EchoQueue::default();
+    let sum = Cell::new(0);
+    let ticks = Cell::new(0);
+
+    let echo = pin!(async {
+        for req in 1..=3 {
+            if queue.push_async(req).await.is_err() {
+                return;
+            }
+            if let Ok(rsp) = queue.pop_async().await {
+                sum.set(sum.get() + rsp);
+            }
+        }
+    });
+    let ticker = pin!(async {
+        for _ in 0..4 {
+            ticks.set(ticks.get() + 1);
+            yield_now().await;
+        }
+    });
+
+    let idles = Cell::new(0);
+    let mut exec: FutureExecutor<'_, _, _, 2> =
+        FutureExecutor::new(&READY, TestTime::default(), || idles.set(idles.get() + 1));
+    let echo = exec.spawn(echo);
+    assert!(exec.spawn(ticker).is_ok());
+    let mut never = pin!(core::future::pending::<()>());
+    assert_eq!(exec.spawn(never.as_mut()).err(), Some(ExecError::Full));
+
+    // first pass: the echo push is pending, the ticker yields once
+    assert!(exec.run_once());
+    assert_eq!(ticks.get(), 1);
+
+    exec.run();
+    assert!(exec.is_empty());
+    assert_eq!(sum.get(), 12);
+    assert_eq!(ticks.get(), 4);
+    // a pending queue wakes nothing, the executor idles before retrying it
+    assert_eq!(idles.get(), 6);
+    assert_eq!(exec.stats().idles, 6);
+    let echo = echo.unwrap();
+    assert!(!exec.is_running(echo));
+
+    // a stale id does not reach the task that took over its slot
+    let mut next = pin!(core::future::pending::<()>());
+    let next = exec.spawn(next.as_mut()).unwrap();
+    assert!(!exec.is_running(echo));
+    assert_eq!(exec.task_stats(echo), None);
+    assert_eq!(exec.cancel(echo), Err(ExecError::NoTask));
+    assert!(exec.is_running(next));
+
+    // nothing woken: the executor idles until the flag is set
+    let mut idle = pin!(core::future::pending::<()>());
+    let mut exec: FutureExecutor<'_, _, _, 2> = FutureExecutor::new(&READY, TestTime::default(), || {});
+    let idle = exec.spawn(idle.as_mut());
+    assert!(exec.run_once());
+    assert!(!exec.run_once());
+    assert_eq!(idle.and_then(|idle| exec.cancel(idle)), Ok(()));
+}
+
+#[test]
+fn time_deadline() {
+    let mut clock = TestTime::default();
+    let start = Instant::now(&mut clock);
+    assert_eq!(start.as_duration(), Duration::from_micros(1));
+    assert_eq!(start.elapsed(&mut clock), Duration::from_micros(1));
+
+    let deadline = Deadline::new(&mut clock, Duration::from_micros(3));
+    assert_eq!(deadline.instant() - start, Duration::from_micros(5));
+    assert_eq!(deadline.remaining(&mut clock), Duration::from_micros(2));
+    assert!(!deadline.is_expired(&mut clock));
+    assert!(deadline.is_expired(&mut clock));
+    assert_eq!(deadline.remaining(&mut clock), Duration::ZERO);
+
+    // every poll reads the clock once, so periods of 3 polls tick every
+    // third call and a late poll folds the missed ticks without drifting
+    let mut interval = Interval::new(&mut clock, Duration::from_micros(3));
+    let first = interval.next();
+    let ticks: std::vec::Vec<_> = (0..6).map(|_| match interval.poll_tick(&mut clock) {
+        Poll::Ready(ticks) => ticks,
+        Poll::Pending => 0,
+    }).collect();
+    assert_eq!(ticks, [0, 0, 1, 0, 0, 1]);
+    clock.now += Duration::from_micros(8);
+    assert!(matches!(interval.poll_tick(&mut clock), Poll::Ready(3)));
+    assert_eq!(interval.next() - first, Duration::from_micros(15));
+}
+
+#[test]
+fn time_sleep_timeout() {
+    let mut clock = TestTime::default();
+
+    let mut nap = time::sleep(&mut clock, Duration::from_micros(2));
+    assert!(matches!(nap.poll_elapsed(), Poll::Pending));
+    assert!(matches!(nap.poll_elapsed(), Poll::Ready(())));
+
+    let mut polls = 0;
+    let mut wait = time::timeout(&mut clock, Duration::from_micros(3), || {
+        polls += 1;
+        match polls {
+            2 => Poll::Ready(polls),
+            _ => Poll::Pending,
+        }
+    });
+    assert!(matches!(wait.poll_op(), Poll::Pending));
+    assert!(matches!(wait.poll_op(), Poll::Ready(Ok(2))));
+
+    let mut wait = time::timeout(&mut clock, Duration::from_micros(1), || Poll::<()>::Pending);
+    assert!(matches!(wait.poll_op(), Poll::Ready(Err(time::Error::Timeout))));
+
+    // async: a stuck future is cut off, a sleep inside the budget is not
+    static READY: [AtomicBool; 2] = [const { AtomicBool::new(false) }; 2];
+    let main: TestRuntime<'_> = RuntimeMain::new(TestTime::default(), NoQueue, |_| IPCByteBuf::default());
+    let results = Cell::new((false, false));
+    let task = pin!(async {
+        let stuck = core::future::pending::<()>();
+        let stuck = time::timeout(main.as_ref(), Duration::from_micros(5), stuck).await;
+        let nap = time::sleep(main.as_ref(), Duration::from_micros(5));
+        let nap = time::timeout(main.as_ref(), Duration::from_micros(50), nap).await;
+        results.set((stuck == Err(time::Error::Timeout), nap == Ok(())));
+    });
+    let mut exec: FutureExecutor<'_, _, _, 2> = FutureExecutor::new(&READY, TestTime::default(), || {});
+    assert!(exec.spawn(task).is_ok());
+    exec.run();
+    assert_eq!(results.get(), (true, true));
+    // every pass without an expired deadline idles instead of spinning
+    assert_eq!(exec.stats().idles, 5);
+}
+
+#[test]
+fn ipcbuf_access() {
+    let mut mem = Aligned([0; 32]);
+    let mut backing = Some(&mut mem.0[..]);
+    let main: RuntimeMain<'_, TestTime, NoQueue, 1, 64, 2> = RuntimeMain::new(
+        TestTime::default(), NoQueue, |_| IPCByteBuf::from(backing.take().unwrap()),
+    );
+    let mut rt = main.as_ref();
+
+    assert_eq!(rt.wr8(0, 0x11), Ok(()));
+    assert_eq!(rt.wr16(2, 0x2233), Ok(()));
+    assert_eq!(rt.wr32_volatile(4, 0x44556677), Ok(()));
+    assert_eq!(rt.wr64_volatile_ordered(8, 0x8899aabbccddeeff), Ok(()));
+    assert_eq!(rt.wr32_atomic(16, 0x01020304), Ok(()));
+
+    // reads used to take the buffers out of the runtime, ask twice
+    for _ in 0..2 {
+        assert_eq!(rt.rd8(0), Ok(0x11));
+        assert_eq!(rt.rd16_volatile(2), Ok(0x2233));
+        assert_eq!(rt.rd32(4), Ok(0x44556677));
+        assert_eq!(rt.rd64_atomic(8), Ok(0x8899aabbccddeeff));
+        assert_eq!(rt.rd32_volatile_ordered(16), Ok(0x01020304));
+    }
+
+    assert_eq!(rt.rd8(32), Err(Error::OutOfBounds));
+    assert_eq!(rt.rd64(28), Err(Error::OutOfBounds));
+    assert_eq!(rt.wr16(usize::MAX, 0), Err(Error::OutOfBounds));
+    assert_eq!(rt.rd32_atomic(2), Err(Error::Unaligned));
+    assert_eq!(rt.wr64_atomic(4, 0), Err(Error::Unaligned));
+    assert_eq!(rt.rd16(1), Ok(0x3300));
+
+    let nested = rt.with_ipcbuf(0, 1, |_| main.as_ref().rd8(0));
+    assert_eq!(nested, Ok(Err(Error::Busy)));
+
+    // out of range selections keep the current buffer
+    rt.ipcbuf(1);
+    assert_eq!(rt.rd8(0), Ok(0x11));
+}
+
+// single slot loopback device, completes with the submitted buffer index
+#[derive(Default)]
+struct LoopQueue {
+    inflight: Option<usize>,
+}
+
+impl Queue for LoopQueue {
+    type Request = usize;
+    type Response = usize;
+    type Error = ();
+
+    fn push(&mut self, req: usize) -> Poll<Result<(), ()>> {
+        if self.inflight.is_some() {
+            return Poll::Pending;
+        }
+        self.inflight = Some(req);
+        Poll::Ready(Ok(()))
+    }
+
+    fn pop(&mut self) -> Poll<Result<usize, ()>> {
+        match self.inflight.take() {
+            Some(idx) => Poll::Ready(Ok(idx)),
+            None => Poll::Pending,
+        }
+    }
+}
+
+// takes every buffer, every completion is an error
+struct ErrQueue;
+
+impl Queue for ErrQueue {
+    type Request = usize;
+    type Response = ();
+    type Error = ();
+
+    fn push(&mut self, _req: usize) -> Poll<Result<(), ()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn pop(&mut self) -> Poll<Result<(), ()>> {
+        Poll::Ready(Err(()))
+    }
+}
+
+#[test]
+fn queue_submit() {
+    let main: RuntimeMain<'_, TestTime, LoopQueue, 2, 64, 2> = RuntimeMain::new(
+        TestTime::default(), LoopQueue::default(), |_| IPCByteBuf::default(),
+    );
+    let mut rt = main.as_ref();
+
+    assert!(matches!(rt.poll(), Poll::Pending));
+    rt.ipcbuf(1);
+    assert!(matches!(rt.submit(), Poll::Ready(Ok(()))));
+    rt.ipcbuf(0);
+    assert!(matches!(rt.submit(), Poll::Pending));
+    assert!(matches!(rt.poll(), Poll::Ready(Ok(1))));
+    assert!(matches!(rt.submit(), Poll::Ready(Ok(()))));
+    assert!(matches!(rt.poll(), Poll::Ready(Ok(0))));
+
+    let main: TestRuntime<'_> = RuntimeMain::new(TestTime::default(), NoQueue, |_| IPCByteBuf::default());
+    assert!(matches!(main.as_ref().submit(), Poll::Ready(Err(()))));
+}
+
+#[test]
+fn queue_inflight() {
+    let (mut first, mut second) = (Aligned([0; 32]), Aligned([0; 32]));
+    let mut backing = [Some(&mut first.0[..]), Some(&mut second.0[..])];
+    let main: RuntimeMain<'_, TestTime, LoopQueue, 2, 64, 2> = RuntimeMain::new(
+        TestTime::default(), LoopQueue::default(), |idx| IPCByteBuf::from(backing[idx].take().unwrap()),
+    );
+    let mut rt = main.as_ref();
+
+    rt.ipcbuf(1);
+    assert_eq!(rt.wr8(0, 0x11), Ok(()));
+    assert!(matches!(rt.submit(), Poll::Ready(Ok(()))));
+
+    // the device owns the buffer until its completion is polled, through
+    // every handle
+    let mut other = main.as_ref();
+    other.ipcbuf(1);
+    assert_eq!(rt.rd8(0), Err(Error::Busy));
+    assert_eq!(other.wr8(0, 0x22), Err(Error::Busy));
+    assert!(matches!(other.submit(), Poll::Pending));
+    other.ipcbuf(0);
+    assert_eq!(other.wr8(0, 0x33), Ok(()));
+
+    // one buffer in flight at a time, a refused submit hands it straight back
+    assert!(matches!(other.submit(), Poll::Pending));
+    assert_eq!(other.rd8(0), Ok(0x33));
+
+    assert!(matches!(rt.poll(), Poll::Ready(Ok(1))));
+    assert_eq!(rt.rd8(0), Ok(0x11));
+    assert_eq!(other.rd8(0), Ok(0x33));
+
+    // an error completion names no buffer, the device keeps it
+    let mut mem = Aligned([0; 32]);
+    let mut backing = Some(&mut mem.0[..]);
+    let main: RuntimeMain<'_, TestTime, ErrQueue, 1, 64, 2> = RuntimeMain::new(
+        TestTime::default(), ErrQueue, |_| IPCByteBuf::from(backing.take().unwrap()),
+    );
+    let mut rt = main.as_ref();
+    assert!(matches!(rt.submit(), Poll::Ready(Ok(()))));
+    assert!(matches!(rt.poll(), Poll::Ready(Err(()))));
+    assert_eq!(rt.rd8(0), Err(Error::Busy));
+}
+
+type SyncRuntime<'a> = RuntimeSync<'a, TestTime, LoopQueue, 1, 1024, 2>;
+
+// logs from inside drain, the way a trap handler on the draining hart would
+struct TrapSink<'a> {
+    main: &'a SyncRuntime<'a>,
+    hart: usize,
+    nested: std::vec::Vec<Result<(), sink::Error>>,
+}
+
+impl LogSink for TrapSink<'_> {
+    fn write(&mut self, _rec: &LogRecord) -> Result<(), sink::Error> {
+        let mut rt = self.main.as_ref(self.hart);
+        crate::info!(rt, "trap");
+        self.nested.push(self.main.for_each_record(self.hart, 0, |_| {}));
+        Ok(())
+    }
+}
+
+#[test]
+fn sync_harts() {
+    const HARTS: usize = 4;
+    const NR: usize = 8;
+
+    let mut mem = Aligned([0; 32]);
+    let mut backing = Some(&mut mem.0[..]);
+    let main: SyncRuntime<'_> = RuntimeSync::new(
+        TestTime::default(), LoopQueue::default(), |_| IPCByteBuf::from(backing.take().unwrap()),
+    );
+
+    std::thread::scope(|scope| {
+        for hart in 0..HARTS {
+            let main = &main;
+            scope.spawn(move || {
+                let mut rt = main.as_ref(hart);
+                rt.logbuf(hart % 2);
+                for nr in 0..NR {
+                    crate::info!(rt, "{} {}", hart, nr);
+                    let off = hart * 8;
+                    assert_eq!(rt.wr64_atomic(off, rt.rd64_atomic(off).unwrap() + 1), Ok(()));
+                    // a second handle on the same hart, as a trap handler
+                    // would take, fails rather than spinning on itself
+                    let nested = rt.with_ipcbuf(off, 8, |_| main.as_ref(hart).rd64(off));
+                    assert_eq!(nested, Ok(Err(Error::Busy)));
+                }
+            });
+        }
+    });
+
+    let mut recs = records_sync(&main, 0);
+    recs.append(&mut records_sync(&main, 1));
+    assert_eq!(recs.len(), HARTS * NR);
+    for hart in 0..HARTS {
+        let msgs = recs.iter().filter(|rec| rec.chan == hart % 2 && rec.msg().starts_with(&std::format!("{} ", hart)));
+        assert_eq!(msgs.count(), NR);
+    }
+
+    let rt = main.as_ref(0);
+    for hart in 0..HARTS {
+        assert_eq!(rt.rd64(hart * 8), Ok(NR as u64));
+    }
+
+    let mut sink: CollectSink<64> = CollectSink::default();
+    assert_eq!(main.drain_all(0, &mut sink), Ok(HARTS * NR));
+    assert_eq!(records_sync(&main, 0).len(), 0);
+
+    // re-entry on the draining hart is refused and its record dropped
+    // instead of spinning on the channel lock
+    let mut rt = main.as_ref(1);
+    crate::info!(rt, "before");
+    let mut trap = TrapSink {
+        main: &main, hart: 1, nested: std::vec::Vec::new(),
+    };
+    assert_eq!(main.drain(1, 0, &mut trap), Ok(1));
+    assert_eq!(trap.nested, [Err(sink::Error::Busy)]);
+    assert_eq!(records_sync(&main, 0).len(), 0);
+}
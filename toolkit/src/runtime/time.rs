@@ -0,0 +1,231 @@
+use core::future::{ Future };
+use core::ops::{ Add, AddAssign, Sub };
+use core::pin::{ Pin };
+use core::task::{ self, Context };
+use core::time::{ Duration };
+use crate::cmd::{ Poll };
+use crate::cmd::future::{ pending };
+use crate::runtime::{ Time };
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Error {
+    Timeout,
+}
+
+/*
+ * point in time on a Time source
+ */
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Instant(Duration);
+
+impl Instant {
+    pub fn now<T: Time + ?Sized>(time: &mut T) -> Self {
+        Self(time.time())
+    }
+
+    pub fn from_duration(since_boot: Duration) -> Self {
+        Self(since_boot)
+    }
+
+    pub fn as_duration(&self) -> Duration {
+        self.0
+    }
+
+    pub fn duration_since(&self, earlier: Instant) -> Duration {
+        self.0.saturating_sub(earlier.0)
+    }
+
+    pub fn elapsed<T: Time + ?Sized>(&self, time: &mut T) -> Duration {
+        Self::now(time).duration_since(*self)
+    }
+
+    pub fn checked_add(&self, dur: Duration) -> Option<Instant> {
+        self.0.checked_add(dur).map(Self)
+    }
+}
+
+impl Add<Duration> for Instant {
+    type Output = Instant;
+
+    fn add(self, dur: Duration) -> Instant {
+        Self(self.0.saturating_add(dur))
+    }
+}
+
+impl AddAssign<Duration> for Instant {
+    fn add_assign(&mut self, dur: Duration) {
+        *self = *self + dur;
+    }
+}
+
+impl Sub<Instant> for Instant {
+    type Output = Duration;
+
+    fn sub(self, earlier: Instant) -> Duration {
+        self.duration_since(earlier)
+    }
+}
+
+/*
+ * deadline
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Deadline {
+    at: Instant,
+}
+
+impl Deadline {
+    pub fn new<T: Time + ?Sized>(time: &mut T, dur: Duration) -> Self {
+        Self::at(Instant::now(time) + dur)
+    }
+
+    pub fn at(at: Instant) -> Self {
+        Self {
+            at,
+        }
+    }
+
+    pub fn instant(&self) -> Instant {
+        self.at
+    }
+
+    pub fn is_expired<T: Time + ?Sized>(&self, time: &mut T) -> bool {
+        Instant::now(time) >= self.at
+    }
+
+    pub fn remaining<T: Time + ?Sized>(&self, time: &mut T) -> Duration {
+        self.at.duration_since(Instant::now(time))
+    }
+}
+
+/*
+ * periodic timer, ticks are scheduled from the previous deadline rather
+ * than from when they were noticed so late polls do not add drift
+ */
+pub struct Interval {
+    next: Instant,
+    period: Duration,
+}
+
+impl Interval {
+    pub fn new<T: Time + ?Sized>(time: &mut T, period: Duration) -> Self {
+        Self {
+            next: Instant::now(time) + period, period,
+        }
+    }
+
+    pub fn period(&self) -> Duration {
+        self.period
+    }
+
+    pub fn next(&self) -> Instant {
+        self.next
+    }
+
+    // number of periods that elapsed since the last tick, missed ticks are
+    // folded into one
+    pub fn poll_tick<T: Time + ?Sized>(&mut self, time: &mut T) -> Poll<u32> {
+        let now = Instant::now(time);
+        if now < self.next || self.period.is_zero() {
+            return Poll::Pending;
+        }
+        let ticks = now.duration_since(self.next).as_nanos() / self.period.as_nanos() + 1;
+        let ticks = u32::try_from(ticks).unwrap_or(u32::MAX);
+        self.next += self.period.saturating_mul(ticks);
+        Poll::Ready(ticks)
+    }
+}
+
+/*
+ * sleep, both a polled state machine and a future
+ */
+pub struct Sleep<T> {
+    time: T,
+    deadline: Deadline,
+}
+
+pub fn sleep<T: Time>(mut time: T, dur: Duration) -> Sleep<T> {
+    let deadline = Deadline::new(&mut time, dur);
+    Sleep {
+        time, deadline,
+    }
+}
+
+impl<T> Sleep<T>
+where T: Time {
+    pub fn deadline(&self) -> Deadline {
+        self.deadline
+    }
+
+    pub fn poll_elapsed(&mut self) -> Poll<()> {
+        match self.deadline.is_expired(&mut self.time) {
+            true => Poll::Ready(()),
+            false => Poll::Pending,
+        }
+    }
+}
+
+impl<T> Future for Sleep<T>
+where T: Time + Unpin {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> task::Poll<()> {
+        match self.get_mut().poll_elapsed() {
+            Poll::Ready(()) => task::Poll::Ready(()),
+            Poll::Pending => pending(),
+        }
+    }
+}
+
+/*
+ * timeout around a polled operation or an Unpin future
+ */
+pub struct Timeout<T, F> {
+    time: T,
+    deadline: Deadline,
+    op: F,
+}
+
+pub fn timeout<T: Time, F>(mut time: T, dur: Duration, op: F) -> Timeout<T, F> {
+    let deadline = Deadline::new(&mut time, dur);
+    Timeout {
+        time, deadline, op,
+    }
+}
+
+impl<T, F> Timeout<T, F>
+where T: Time {
+    pub fn deadline(&self) -> Deadline {
+        self.deadline
+    }
+
+    pub fn into_inner(self) -> F {
+        self.op
+    }
+
+    // the operation gets one last poll once the deadline has passed
+    pub fn poll_op<R>(&mut self) -> Poll<Result<R, Error>>
+    where F: FnMut() -> Poll<R> {
+        let expired = self.deadline.is_expired(&mut self.time);
+        match ((self.op)(), expired) {
+            (Poll::Ready(value), _) => Poll::Ready(Ok(value)),
+            (Poll::Pending, true) => Poll::Ready(Err(Error::Timeout)),
+            (Poll::Pending, false) => Poll::Pending,
+        }
+    }
+}
+
+impl<T, F> Future for Timeout<T, F>
+where T: Time + Unpin, F: Future + Unpin {
+    type Output = Result<F::Output, Error>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> task::Poll<Self::Output> {
+        let this = self.get_mut();
+        let expired = this.deadline.is_expired(&mut this.time);
+        match (Pin::new(&mut this.op).poll(cx), expired) {
+            (task::Poll::Ready(value), _) => task::Poll::Ready(Ok(value)),
+            (task::Poll::Pending, true) => task::Poll::Ready(Err(Error::Timeout)),
+            (task::Poll::Pending, false) => pending(),
+        }
+    }
+}
@@ -0,0 +1,75 @@
+use crate::bytebuf::{ VolatileByteBuf };
+
+pub mod uart;
+pub mod virtio;
+
+#[cfg(test)]
+mod test;
+
+pub trait Device {
+    fn on_read(&mut self, off: usize, width: u8) -> u64;
+    fn on_write(&mut self, off: usize, width: u8, value: u64);
+}
+
+/*
+ * simulated mmio window
+ */
+pub struct SimByteBuf<D> {
+    dev: D,
+}
+
+impl<D> SimByteBuf<D>
+where D: Device {
+    pub fn new(dev: D) -> Self {
+        Self {
+            dev,
+        }
+    }
+
+    pub fn dev(&self) -> &D {
+        &self.dev
+    }
+
+    pub fn dev_mut(&mut self) -> &mut D {
+        &mut self.dev
+    }
+
+    pub fn into_inner(self) -> D {
+        self.dev
+    }
+}
+
+impl<D> VolatileByteBuf for SimByteBuf<D>
+where D: Device {
+    fn rd8_volatile(&mut self, off: usize) -> u8 {
+        self.dev.on_read(off, 1) as u8
+    }
+
+    fn wr8_volatile(&mut self, off: usize, value: u8) {
+        self.dev.on_write(off, 1, value.into());
+    }
+
+    fn rd16_volatile(&mut self, off: usize) -> u16 {
+        self.dev.on_read(off, 2) as u16
+    }
+
+    fn wr16_volatile(&mut self, off: usize, value: u16) {
+        self.dev.on_write(off, 2, value.into());
+    }
+
+    fn rd32_volatile(&mut self, off: usize) -> u32 {
+        self.dev.on_read(off, 4) as u32
+    }
+
+    fn wr32_volatile(&mut self, off: usize, value: u32) {
+        self.dev.on_write(off, 4, value.into());
+    }
+
+    fn rd64_volatile(&mut self, off: usize) -> u64 {
+        self.dev.on_read(off, 8)
+    }
+
+    fn wr64_volatile(&mut self, off: usize, value: u64) {
+        self.dev.on_write(off, 8, value);
+    }
+}
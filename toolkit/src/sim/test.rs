@@ -0,0 +1,91 @@
+use crate::bytebuf::{ VolatileByteBuf };
+use crate::sim::{ SimByteBuf };
+use crate::sim::uart::{ Uart16550 };
+use crate::sim::virtio::{ self, VirtioMmio };
+use crate::uart::{ Uart, iir, lcr, lsr };
+
+const VIRTIO_NET: u32 = 1;
+const VIRTIO_STATUS_ACK: u32 = 1;
+const VIRTIO_STATUS_DRIVER: u32 = 2;
+const VIRTIO_STATUS_FEATURES_OK: u32 = 8;
+
+#[test]
+fn uart_echo() {
+    let mut uart = Uart::new(SimByteBuf::new(Uart16550::<4>::default()));
+
+    uart.lcr().modify_field(lcr::DLAB, 1);
+    uart.dll().write(3);
+    uart.lcr().write(0);
+    uart.lcr().modify_field(lcr::WLS, 3);
+    assert_eq!(uart.lcr().read(), 0x03);
+
+    assert!(uart.iir().is_set(iir::NONE));
+    assert!(!uart.lsr().is_set(lsr::DR));
+
+    let mut io = uart.into_inner();
+    assert_eq!(io.dev().divisor(), 3);
+    assert!(io.dev_mut().push_rx(b'h'));
+    assert!(io.dev_mut().push_rx(b'i'));
+    let mut uart = Uart::new(io);
+
+    uart.ier().write(1);
+    assert_eq!(uart.iir().read_field(iir::ID), 2);
+    while uart.lsr().is_set(lsr::DR) {
+        let byte = uart.rbr().read();
+        while !uart.lsr().is_set(lsr::THRE) { }
+        uart.thr().write(byte.to_ascii_uppercase());
+    }
+    assert!(uart.iir().is_set(iir::NONE));
+
+    let mut io = uart.into_inner();
+    assert_eq!(io.dev_mut().pop_tx(), Some(b'H'));
+    assert_eq!(io.dev_mut().pop_tx(), Some(b'I'));
+    assert_eq!(io.dev_mut().pop_tx(), None);
+}
+
+#[test]
+fn virtio_handshake() {
+    let mut io = SimByteBuf::new(VirtioMmio::<2, 8>::new(VIRTIO_NET, 0x1_0000_0020, 256));
+    io.dev_mut().set_config(0, &[0x52, 0x54, 0x00, 0x12, 0x34, 0x56]);
+
+    assert_eq!(io.rd32_volatile(0x000), virtio::VIRTIO_MAGIC);
+    assert_eq!(io.rd32_volatile(0x004), virtio::VIRTIO_VERSION);
+    assert_eq!(io.rd32_volatile(0x008), VIRTIO_NET);
+
+    io.wr32_volatile(0x070, VIRTIO_STATUS_ACK | VIRTIO_STATUS_DRIVER);
+    io.wr32_volatile(0x014, 1);
+    assert_eq!(io.rd32_volatile(0x010), 1);
+    io.wr32_volatile(0x024, 1);
+    io.wr32_volatile(0x020, 1);
+    io.wr32_volatile(0x070, VIRTIO_STATUS_ACK | VIRTIO_STATUS_DRIVER | VIRTIO_STATUS_FEATURES_OK);
+    assert_eq!(io.dev().driver_features(), 0x1_0000_0000);
+
+    io.wr32_volatile(0x030, 1);
+    assert_eq!(io.rd32_volatile(0x034), 256);
+    io.wr32_volatile(0x038, 512);
+    io.wr32_volatile(0x080, 0x8000_1000);
+    io.wr32_volatile(0x084, 0x1);
+    io.wr32_volatile(0x044, 1);
+    let queue = io.dev().queue(1).copied().unwrap();
+    assert_eq!(queue.num, 256);
+    assert_eq!(queue.desc, 0x1_8000_1000);
+    assert!(queue.ready);
+
+    io.wr32_volatile(0x050, 1);
+    assert!(io.dev_mut().take_notify(1));
+    assert!(!io.dev_mut().take_notify(1));
+
+    io.dev_mut().raise_interrupt(1);
+    assert_eq!(io.rd32_volatile(0x060), 1);
+    io.wr32_volatile(0x064, 1);
+    assert_eq!(io.rd32_volatile(0x060), 0);
+
+    assert_eq!(io.rd8_volatile(0x100), 0x52);
+    assert_eq!(io.rd16_volatile(0x104), 0x5634);
+    assert_eq!(io.rd32_volatile(0x0FC), 1);
+
+    io.wr32_volatile(0x070, 0);
+    assert_eq!(io.dev().status(), 0);
+    assert_eq!(io.dev().queue(1).map(|queue| queue.ready), Some(false));
+    assert_eq!(io.rd8_volatile(0x105), 0x56);
+}
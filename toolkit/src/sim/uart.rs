@@ -0,0 +1,103 @@
+use crate::collection::deque::{ Deque };
+use crate::sim::{ Device };
+use crate::uart::{ rbr, ier, iir, fcr, lcr, mcr, lsr, msr, scr };
+
+// interrupt ids reported in iir.ID
+const IIR_ID_TX: u8 = 1;
+const IIR_ID_RX: u8 = 2;
+
+/*
+ * 16550 model
+ */
+#[derive(Default)]
+pub struct Uart16550<const L: usize> {
+    rx: Deque<u8, L>,
+    tx: Deque<u8, L>,
+    ier: u8,
+    fcr: u8,
+    lcr: u8,
+    mcr: u8,
+    scr: u8,
+    dll: u8,
+    dlm: u8,
+}
+
+impl<const L: usize>
+Uart16550<L> {
+    pub fn push_rx(&mut self, byte: u8) -> bool {
+        if self.rx.is_full() {
+            return false;
+        }
+        self.rx.push(byte);
+        true
+    }
+
+    pub fn pop_tx(&mut self) -> Option<u8> {
+        self.tx.pop()
+    }
+
+    pub fn divisor(&self) -> u16 {
+        u16::from_le_bytes([self.dll, self.dlm])
+    }
+
+    fn dlab(&self) -> bool {
+        lcr::DLAB.get(self.lcr) != 0
+    }
+
+    fn lsr(&self) -> u8 {
+        let mut reg = 0;
+        reg = lsr::DR.set(reg, (!self.rx.is_empty()).into());
+        reg = lsr::THRE.set(reg, (!self.tx.is_full()).into());
+        lsr::TEMT.set(reg, self.tx.is_empty().into())
+    }
+
+    fn iir(&self) -> u8 {
+        let fifo = match fcr::FIFO.get(self.fcr) {
+            0 => 0,
+            _ => iir::FIFO.set(0, 0x3),
+        };
+        let reg = self.lsr();
+        if ier::RX.get(self.ier) != 0 && lsr::DR.get(reg) != 0 {
+            return iir::ID.set(fifo, IIR_ID_RX);
+        }
+        if ier::TX.get(self.ier) != 0 && lsr::THRE.get(reg) != 0 {
+            return iir::ID.set(fifo, IIR_ID_TX);
+        }
+        iir::NONE.set(fifo, 1)
+    }
+}
+
+impl<const L: usize>
+Device for Uart16550<L> {
+    fn on_read(&mut self, off: usize, _width: u8) -> u64 {
+        let value = match off {
+            rbr::OFFSET if self.dlab() => self.dll,
+            rbr::OFFSET => self.rx.pop().unwrap_or(0),
+            ier::OFFSET if self.dlab() => self.dlm,
+            ier::OFFSET => self.ier,
+            iir::OFFSET => self.iir(),
+            lcr::OFFSET => self.lcr,
+            mcr::OFFSET => self.mcr,
+            lsr::OFFSET => self.lsr(),
+            msr::OFFSET => 0,
+            scr::OFFSET => self.scr,
+            _ => 0,
+        };
+        value.into()
+    }
+
+    fn on_write(&mut self, off: usize, _width: u8, value: u64) {
+        let value = value as u8;
+        match off {
+            rbr::OFFSET if self.dlab() => self.dll = value,
+            rbr::OFFSET => self.tx.push(value),
+            ier::OFFSET if self.dlab() => self.dlm = value,
+            ier::OFFSET => self.ier = value & 0x0F,
+            fcr::OFFSET => self.fcr = value,
+            lcr::OFFSET => self.lcr = value,
+            mcr::OFFSET => self.mcr = value,
+            scr::OFFSET => self.scr = value,
+            _ => { },
+        }
+    }
+}
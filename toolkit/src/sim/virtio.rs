@@ -0,0 +1,206 @@
+use crate::sim::{ Device };
+
+pub const VIRTIO_MAGIC: u32 = 0x7472_6976;
+pub const VIRTIO_VERSION: u32 = 2;
+pub const VIRTIO_VENDOR: u32 = 0x554D_4551;
+
+const VIRTIO_MAGIC_VALUE: usize = 0x000;
+const VIRTIO_VERSION_REG: usize = 0x004;
+const VIRTIO_DEVICE_ID: usize = 0x008;
+const VIRTIO_VENDOR_ID: usize = 0x00C;
+const VIRTIO_DEVICE_FEATURES: usize = 0x010;
+const VIRTIO_DEVICE_FEATURES_SEL: usize = 0x014;
+const VIRTIO_DRIVER_FEATURES: usize = 0x020;
+const VIRTIO_DRIVER_FEATURES_SEL: usize = 0x024;
+const VIRTIO_QUEUE_SEL: usize = 0x030;
+const VIRTIO_QUEUE_NUM_MAX: usize = 0x034;
+const VIRTIO_QUEUE_NUM: usize = 0x038;
+const VIRTIO_QUEUE_READY: usize = 0x044;
+const VIRTIO_QUEUE_NOTIFY: usize = 0x050;
+const VIRTIO_INTERRUPT_STATUS: usize = 0x060;
+const VIRTIO_INTERRUPT_ACK: usize = 0x064;
+const VIRTIO_STATUS: usize = 0x070;
+const VIRTIO_QUEUE_DESC_LOW: usize = 0x080;
+const VIRTIO_QUEUE_DESC_HIGH: usize = 0x084;
+const VIRTIO_QUEUE_DRIVER_LOW: usize = 0x090;
+const VIRTIO_QUEUE_DRIVER_HIGH: usize = 0x094;
+const VIRTIO_QUEUE_DEVICE_LOW: usize = 0x0A0;
+const VIRTIO_QUEUE_DEVICE_HIGH: usize = 0x0A4;
+const VIRTIO_CONFIG_GENERATION: usize = 0x0FC;
+const VIRTIO_CONFIG: usize = 0x100;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct SimQueue {
+    pub num_max: u32,
+    pub num: u32,
+    pub ready: bool,
+    pub desc: u64,
+    pub driver: u64,
+    pub device: u64,
+}
+
+fn set_low(reg: &mut u64, value: u64) {
+    *reg = (*reg & !0xFFFF_FFFF) | (value & 0xFFFF_FFFF);
+}
+
+fn set_high(reg: &mut u64, value: u64) {
+    *reg = (*reg & 0xFFFF_FFFF) | ((value & 0xFFFF_FFFF) << 32);
+}
+
+/*
+ * virtio-mmio header model
+ */
+pub struct VirtioMmio<const QNR: usize, const CFGL: usize> {
+    device_id: u32,
+    device_features: u64,
+    num_max: u32,
+    device_features_sel: u32,
+    driver_features: u64,
+    driver_features_sel: u32,
+    status: u32,
+    queue_sel: usize,
+    queues: [SimQueue; QNR],
+    notify: u64,
+    interrupt: u32,
+    generation: u32,
+    config: [u8; CFGL],
+}
+
+impl<const QNR: usize, const CFGL: usize>
+VirtioMmio<QNR, CFGL> {
+    pub fn new(device_id: u32, device_features: u64, num_max: u32) -> Self {
+        Self {
+            device_id, device_features, num_max,
+            device_features_sel: 0,
+            driver_features: 0,
+            driver_features_sel: 0,
+            status: 0,
+            queue_sel: 0,
+            queues: [SimQueue { num_max, ..SimQueue::default() }; QNR],
+            notify: 0,
+            interrupt: 0,
+            generation: 0,
+            config: [0; CFGL],
+        }
+    }
+
+    pub fn status(&self) -> u32 {
+        self.status
+    }
+
+    pub fn driver_features(&self) -> u64 {
+        self.driver_features
+    }
+
+    pub fn queue(&self, idx: usize) -> Option<&SimQueue> {
+        self.queues.get(idx)
+    }
+
+    pub fn take_notify(&mut self, idx: usize) -> bool {
+        let bit = 1u64.checked_shl(idx as u32).unwrap_or(0);
+        let notified = self.notify & bit != 0;
+        self.notify &= !bit;
+        notified
+    }
+
+    pub fn raise_interrupt(&mut self, bits: u32) {
+        self.interrupt |= bits;
+    }
+
+    pub fn set_config(&mut self, off: usize, bytes: &[u8]) {
+        for (idx, byte) in bytes.iter().enumerate() {
+            if let Some(cfg) = self.config.get_mut(off + idx) {
+                *cfg = *byte;
+            }
+        }
+        self.generation = self.generation.wrapping_add(1);
+    }
+
+    fn reset(&mut self) {
+        *self = Self {
+            config: self.config,
+            generation: self.generation,
+            ..Self::new(self.device_id, self.device_features, self.num_max)
+        };
+    }
+
+    fn read_config(&self, off: usize, width: u8) -> u64 {
+        let mut bytes = [0; 8];
+        for (idx, byte) in bytes.iter_mut().take(width.into()).enumerate() {
+            *byte = self.config.get(off + idx).copied().unwrap_or(0);
+        }
+        u64::from_le_bytes(bytes)
+    }
+}
+
+impl<const QNR: usize, const CFGL: usize>
+Device for VirtioMmio<QNR, CFGL> {
+    fn on_read(&mut self, off: usize, width: u8) -> u64 {
+        if off >= VIRTIO_CONFIG {
+            return self.read_config(off - VIRTIO_CONFIG, width);
+        }
+        let queue = self.queues.get(self.queue_sel).copied().unwrap_or_default();
+        let value = match off {
+            VIRTIO_MAGIC_VALUE => VIRTIO_MAGIC,
+            VIRTIO_VERSION_REG => VIRTIO_VERSION,
+            VIRTIO_DEVICE_ID => self.device_id,
+            VIRTIO_VENDOR_ID => VIRTIO_VENDOR,
+            VIRTIO_DEVICE_FEATURES => match self.device_features_sel {
+                0 => self.device_features as u32,
+                1 => (self.device_features >> 32) as u32,
+                _ => 0,
+            },
+            VIRTIO_QUEUE_NUM_MAX => queue.num_max,
+            VIRTIO_QUEUE_NUM => queue.num,
+            VIRTIO_QUEUE_READY => queue.ready.into(),
+            VIRTIO_INTERRUPT_STATUS => self.interrupt,
+            VIRTIO_STATUS => self.status,
+            VIRTIO_QUEUE_DESC_LOW => queue.desc as u32,
+            VIRTIO_QUEUE_DESC_HIGH => (queue.desc >> 32) as u32,
+            VIRTIO_QUEUE_DRIVER_LOW => queue.driver as u32,
+            VIRTIO_QUEUE_DRIVER_HIGH => (queue.driver >> 32) as u32,
+            VIRTIO_QUEUE_DEVICE_LOW => queue.device as u32,
+            VIRTIO_QUEUE_DEVICE_HIGH => (queue.device >> 32) as u32,
+            VIRTIO_CONFIG_GENERATION => self.generation,
+            _ => 0,
+        };
+        value.into()
+    }
+
+    fn on_write(&mut self, off: usize, _width: u8, value: u64) {
+        let queue_sel = self.queue_sel;
+        match off {
+            VIRTIO_DEVICE_FEATURES_SEL => self.device_features_sel = value as u32,
+            VIRTIO_DRIVER_FEATURES => match self.driver_features_sel {
+                0 => set_low(&mut self.driver_features, value),
+                1 => set_high(&mut self.driver_features, value),
+                _ => { },
+            },
+            VIRTIO_DRIVER_FEATURES_SEL => self.driver_features_sel = value as u32,
+            VIRTIO_QUEUE_SEL => self.queue_sel = value as usize,
+            VIRTIO_QUEUE_NOTIFY => {
+                self.notify |= 1u64.checked_shl(value as u32).unwrap_or(0);
+            },
+            VIRTIO_INTERRUPT_ACK => self.interrupt &= !(value as u32),
+            VIRTIO_STATUS => match value {
+                0 => self.reset(),
+                _ => self.status = value as u32,
+            },
+            _ => { },
+        }
+        let Some(queue) = self.queues.get_mut(queue_sel) else {
+            return;
+        };
+        match off {
+            VIRTIO_QUEUE_NUM => queue.num = (value as u32).min(queue.num_max),
+            VIRTIO_QUEUE_READY => queue.ready = value & 1 != 0,
+            VIRTIO_QUEUE_DESC_LOW => set_low(&mut queue.desc, value),
+            VIRTIO_QUEUE_DESC_HIGH => set_high(&mut queue.desc, value),
+            VIRTIO_QUEUE_DRIVER_LOW => set_low(&mut queue.driver, value),
+            VIRTIO_QUEUE_DRIVER_HIGH => set_high(&mut queue.driver, value),
+            VIRTIO_QUEUE_DEVICE_LOW => set_low(&mut queue.device, value),
+            VIRTIO_QUEUE_DEVICE_HIGH => set_high(&mut queue.device, value),
+            _ => { },
+        }
+    }
+}
@@ -0,0 +1,37 @@
+use crate::register_block;
+
+/*
+ * 16550 register map, dll/dlm alias rbr/ier while lcr.DLAB is set
+ */
+register_block! {
+    pub struct Uart {
+        0x00 => rbr: u8, RO,
+        0x00 => thr: u8, WO,
+        0x00 => dll: u8, RW,
+        0x01 => ier: u8, RW {
+            RX[0],
+            TX[1],
+        },
+        0x01 => dlm: u8, RW,
+        0x02 => iir: u8, RO {
+            NONE[0],
+            ID[3:1],
+            FIFO[7:6],
+        },
+        0x02 => fcr: u8, WO {
+            FIFO[0],
+        },
+        0x03 => lcr: u8, RW {
+            WLS[1:0],
+            DLAB[7],
+        },
+        0x04 => mcr: u8, RW,
+        0x05 => lsr: u8, RO {
+            DR[0],
+            THRE[5],
+            TEMT[6],
+        },
+        0x06 => msr: u8, RO,
+        0x07 => scr: u8, RW,
+    }
+}
@@ -0,0 +1,32 @@
+use core::sync::atomic::{ self, Ordering };
+
+#[cfg(any(target_arch = "riscv32", target_arch = "riscv64"))]
+use core::arch::{ asm };
+
+pub fn compiler() {
+    atomic::compiler_fence(Ordering::SeqCst);
+}
+
+pub fn full() {
+    atomic::fence(Ordering::SeqCst);
+}
+
+pub fn acquire() {
+    atomic::fence(Ordering::Acquire);
+}
+
+pub fn release() {
+    atomic::fence(Ordering::Release);
+}
+
+// fence(SeqCst) is `fence rw, rw` on RISC-V, which leaves device I/O unordered
+#[cfg(any(target_arch = "riscv32", target_arch = "riscv64"))]
+pub fn io() {
+    atomic::compiler_fence(Ordering::SeqCst);
+    unsafe { asm!("fence iorw, iorw", options(nostack, preserves_flags)); }
+}
+
+#[cfg(not(any(target_arch = "riscv32", target_arch = "riscv64")))]
+pub fn io() {
+    atomic::fence(Ordering::SeqCst);
+}
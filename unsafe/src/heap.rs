@@ -0,0 +1,184 @@
+use core::alloc::{ GlobalAlloc, Layout };
+use core::cell::{ UnsafeCell };
+use core::hint::{ self };
+use core::mem::{ self };
+use core::ptr::{ self };
+use core::sync::atomic::{ AtomicBool, Ordering };
+use crate::{ IPCByteBuf };
+
+#[cfg(test)]
+mod test;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct HeapStats {
+    pub total: usize,
+    pub used: usize,
+    pub free: usize,
+    pub allocs: usize,
+    pub peak: usize,
+}
+
+#[repr(align(16))]
+struct Block {
+    len: usize,
+    next: *mut Block,
+}
+
+const BLOCK_LEN: usize = mem::size_of::<Block>();
+const BLOCK_ALIGN: usize = mem::align_of::<Block>();
+
+fn block_len(layout: &Layout) -> usize {
+    layout.size().max(BLOCK_LEN).next_multiple_of(BLOCK_LEN)
+}
+
+/*
+ * free list
+ */
+struct HeapInner {
+    head: *mut Block,
+    stats: HeapStats,
+}
+
+impl HeapInner {
+    unsafe fn alloc(&mut self, layout: &Layout) -> *mut u8 {
+        let len = block_len(layout);
+        let align = layout.align().max(BLOCK_ALIGN);
+        let mut prev: *mut *mut Block = &mut self.head;
+        unsafe {
+            while !(*prev).is_null() {
+                let block = *prev;
+                let start = block.addr();
+                let end = start + (*block).len;
+                let mut addr = start.next_multiple_of(align);
+                if addr != start && addr - start < BLOCK_LEN {
+                    addr = (start + BLOCK_LEN).next_multiple_of(align);
+                }
+                if addr + len > end {
+                    prev = &mut (*block).next;
+                    continue;
+                }
+                let mut next = (*block).next;
+                if addr + len < end {
+                    let tail = block.byte_add(addr + len - start);
+                    tail.write(Block {
+                        len: end - addr - len, next,
+                    });
+                    next = tail;
+                }
+                if addr > start {
+                    (*block).len = addr - start;
+                    (*block).next = next;
+                } else {
+                    *prev = next;
+                }
+                self.stats.used += len;
+                self.stats.free -= len;
+                self.stats.allocs += 1;
+                self.stats.peak = self.stats.peak.max(self.stats.used);
+                return block.byte_add(addr - start).cast::<u8>();
+            }
+        }
+        ptr::null_mut()
+    }
+
+    unsafe fn free(&mut self, addr: *mut u8, len: usize) {
+        let block = addr.cast::<Block>();
+        let mut prev: *mut Block = ptr::null_mut();
+        let mut next = self.head;
+        unsafe {
+            while !next.is_null() && next.addr() < block.addr() {
+                prev = next;
+                next = (*next).next;
+            }
+            block.write(Block {
+                len, next,
+            });
+            if !next.is_null() && block.addr() + len == next.addr() {
+                (*block).len += (*next).len;
+                (*block).next = (*next).next;
+            }
+            if prev.is_null() {
+                self.head = block;
+                return;
+            }
+            (*prev).next = block;
+            if prev.addr() + (*prev).len == block.addr() {
+                (*prev).len += (*block).len;
+                (*prev).next = (*block).next;
+            }
+        }
+    }
+}
+
+/*
+ * global heap
+ */
+pub struct Heap {
+    lock: AtomicBool,
+    inner: UnsafeCell<HeapInner>,
+}
+
+unsafe impl Sync for Heap { }
+
+impl Default for Heap {
+    fn default() -> Self {
+        Self::empty()
+    }
+}
+
+impl Heap {
+    pub const fn empty() -> Self {
+        Self {
+            lock: AtomicBool::new(false),
+            inner: UnsafeCell::new(HeapInner {
+                head: ptr::null_mut(),
+                stats: HeapStats {
+                    total: 0, used: 0, free: 0, allocs: 0, peak: 0,
+                },
+            }),
+        }
+    }
+
+    fn with<R, F: FnOnce(&mut HeapInner) -> R>(&self, f: F) -> R {
+        while self.lock.compare_exchange_weak(false, true, Ordering::Acquire, Ordering::Relaxed).is_err() {
+            hint::spin_loop();
+        }
+        let ret = f(unsafe { &mut *self.inner.get() });
+        self.lock.store(false, Ordering::Release);
+        ret
+    }
+
+    pub fn init(&self, mem: IPCByteBuf<'static>) {
+        let start = mem.buf.as_mut_ptr();
+        let pad = start.addr().next_multiple_of(BLOCK_ALIGN) - start.addr();
+        let len = mem.len().saturating_sub(pad) / BLOCK_LEN * BLOCK_LEN;
+        if len == 0 {
+            return;
+        }
+        self.with(|inner| {
+            unsafe { inner.free(start.wrapping_add(pad), len); }
+            inner.stats.total += len;
+            inner.stats.free += len;
+        });
+    }
+
+    pub fn stats(&self) -> HeapStats {
+        self.with(|inner| inner.stats)
+    }
+}
+
+unsafe impl GlobalAlloc for Heap {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        self.with(|inner| unsafe { inner.alloc(&layout) })
+    }
+
+    unsafe fn dealloc(&self, addr: *mut u8, layout: Layout) {
+        let len = block_len(&layout);
+        self.with(|inner| {
+            unsafe { inner.free(addr, len); }
+            inner.stats.used -= len;
+            inner.stats.free += len;
+            inner.stats.allocs -= 1;
+        });
+    }
+}
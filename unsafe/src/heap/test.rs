@@ -0,0 +1,50 @@
+extern crate std;
+
+use core::alloc::{ GlobalAlloc, Layout };
+use std::boxed::{ Box };
+use crate::{ IPCByteBuf };
+use crate::heap::{ Heap, HeapStats };
+
+const HEAPLEN: usize = 4096;
+
+#[test]
+fn heap_alloc_free() {
+    let mem: &'static mut [u8] = Box::leak(Box::new([0u8; HEAPLEN + 8]));
+    let heap = Heap::empty();
+    heap.init(IPCByteBuf::from(&mut mem[8..]));
+
+    let total = heap.stats().total;
+    assert!(total >= HEAPLEN - 16);
+    assert_eq!(heap.stats(), HeapStats {
+        total, used: 0, free: total, allocs: 0, peak: 0,
+    });
+
+    let small = Layout::from_size_align(3, 1).unwrap();
+    let aligned = Layout::from_size_align(100, 256).unwrap();
+    unsafe {
+        let first = heap.alloc(small);
+        let second = heap.alloc(aligned);
+        let third = heap.alloc(small);
+        assert!(!first.is_null() && !second.is_null() && !third.is_null());
+        assert_eq!(second.addr() % 256, 0);
+        assert_eq!(heap.stats().allocs, 3);
+        assert_eq!(heap.stats().used, 16 + 112 + 16);
+
+        second.write_bytes(0xA5, 100);
+        assert!(heap.alloc(Layout::from_size_align(HEAPLEN, 16).unwrap()).is_null());
+
+        heap.dealloc(second, aligned);
+        heap.dealloc(first, small);
+        heap.dealloc(third, small);
+    }
+    assert_eq!(heap.stats(), HeapStats {
+        total, used: 0, free: total, allocs: 0, peak: 16 + 112 + 16,
+    });
+
+    let whole = Layout::from_size_align(total, 16).unwrap();
+    unsafe {
+        let all = heap.alloc(whole);
+        assert!(!all.is_null());
+        heap.dealloc(all, whole);
+    }
+}
@@ -0,0 +1,34 @@
+use core::ptr::{ self };
+
+// interned strings live nul terminated in their own section, their id is
+// the offset from the section start so host tools can resolve them from
+// the elf without knowing the load address
+unsafe extern "C" {
+    static __start_toolkit_fmt: u8;
+}
+
+pub const fn bytes<const N: usize>(s: &str) -> [u8; N] {
+    let mut out = [0; N];
+    let mut idx = 0;
+    while idx < s.len() && idx < N {
+        out[idx] = s.as_bytes()[idx];
+        idx += 1;
+    }
+    out
+}
+
+#[inline]
+pub fn base() -> usize {
+    ptr::addr_of!(__start_toolkit_fmt) as usize
+}
+
+#[macro_export]
+macro_rules! intern {
+    ($s:expr) => {{
+        const S: &str = $s;
+        #[used]
+        #[unsafe(link_section = "toolkit_fmt")]
+        static INTERN: [u8; S.len() + 1] = $crate::intern::bytes(S);
+        core::ptr::addr_of!(INTERN) as usize - $crate::intern::base()
+    }};
+}
@@ -1,9 +1,28 @@
 #![no_std]
 
+pub mod registry;
+pub mod plain;
+pub mod fence;
+pub mod intern;
+
+#[cfg(target_has_atomic = "8")]
+pub mod waker;
+
+#[cfg(target_has_atomic = "ptr")]
+pub mod spin;
+
+#[cfg(feature = "alloc")]
+pub mod heap;
+
+#[cfg(test)]
+mod test;
+
 use core::ptr::{ self };
 use core::mem::{ self };
-use core::slice::{ self };
+use core::slice::{ self, ChunksExactMut };
+use core::ops::{ Bound, RangeBounds };
 use core::sync::atomic::{ Ordering };
+use crate::plain::{ FromBytes, AsBytes };
 
 #[cfg(target_has_atomic = "8")]
 use core::sync::atomic::{ AtomicU8 };
@@ -17,12 +36,25 @@ use core::sync::atomic::{ AtomicU32 };
 #[cfg(target_has_atomic = "64")]
 use core::sync::atomic::{ AtomicU64 };
 
+#[derive(Default)]
 pub struct IPCByteBuf<'a> {
     buf: &'a mut [u8],
 }
 
+impl<'a> From<&'a mut [u8]> for IPCByteBuf<'a> {
+    fn from(buf: &'a mut [u8]) -> Self {
+        Self {
+            buf,
+        }
+    }
+}
+
 impl IPCByteBuf<'_> {
-    pub fn new(addr: usize, len: usize) -> Self {
+    /// # Safety
+    ///
+    /// `addr..addr + len` must be valid, exclusively owned memory for the
+    /// whole lifetime of the buffer.
+    pub unsafe fn new(addr: usize, len: usize) -> Self {
         Self {
             buf: unsafe {
                 slice::from_raw_parts_mut(ptr::with_exposed_provenance_mut(addr), len)
@@ -38,12 +70,55 @@ impl IPCByteBuf<'_> {
         self.buf.len()
     }
 
+    pub fn is_empty(&self) -> bool {
+        self.buf.is_empty()
+    }
+
+    pub fn slice<R: RangeBounds<usize>>(&mut self, range: R) -> Option<IPCByteBuf<'_>> {
+        let bounds: (Bound<usize>, Bound<usize>) = (
+            range.start_bound().cloned(), range.end_bound().cloned(),
+        );
+        Some(IPCByteBuf {
+            buf: self.buf.get_mut(bounds)?,
+        })
+    }
+
+    pub fn split_at(&mut self, mid: usize) -> Option<(IPCByteBuf<'_>, IPCByteBuf<'_>)> {
+        let (left, right) = self.buf.split_at_mut_checked(mid)?;
+        Some((IPCByteBuf { buf: left }, IPCByteBuf { buf: right }))
+    }
+
+    // bytes that do not divide evenly are left to into_remainder
+    pub fn split_into_chunks(&mut self, nr: usize) -> IPCByteBufChunks<'_> {
+        let len = self.buf.len().checked_div(nr).unwrap_or(0);
+        let chunks = match len {
+            0 => {
+                let len = self.buf.len().saturating_add(1);
+                self.buf.chunks_exact_mut(len)
+            },
+            _ => self.buf.chunks_exact_mut(len),
+        };
+        IPCByteBufChunks {
+            chunks,
+        }
+    }
+
     fn off<T>(&self, off: usize) -> *const T {
-        (&self.buf[off..off+mem::size_of::<T>()]).as_ptr().cast::<T>()
+        self.buf[off..off+mem::size_of::<T>()].as_ptr().cast::<T>()
     }
 
     fn off_mut<T>(&mut self, off: usize) -> *mut T {
-        (&mut self.buf[off..off+mem::size_of::<T>()]).as_mut_ptr().cast::<T>()
+        self.buf[off..off+mem::size_of::<T>()].as_mut_ptr().cast::<T>()
+    }
+
+    pub fn read_struct<T: FromBytes>(&self, off: usize) -> T {
+        let addr = self.off::<T>(off);
+        unsafe { addr.read_unaligned() }
+    }
+
+    pub fn write_struct<T: AsBytes>(&mut self, off: usize, value: &T) {
+        let len = mem::size_of::<T>();
+        self.buf[off..off+len].copy_from_slice(value.as_bytes());
     }
 
     pub fn rd8(&self, off: usize) -> u8 {
@@ -74,6 +149,17 @@ impl IPCByteBuf<'_> {
         unsafe { addr.write_volatile(value); }
     }
 
+    pub fn rd8_volatile_ordered(&self, off: usize) -> u8 {
+        let value = self.rd8_volatile(off);
+        fence::io();
+        value
+    }
+
+    pub fn wr8_volatile_ordered(&mut self, off: usize, value: u8) {
+        fence::io();
+        self.wr8_volatile(off, value);
+    }
+
     #[cfg(target_has_atomic = "8")]
     pub fn wr8_atomic(&mut self, off: usize, value: u8) {
         unsafe {
@@ -110,6 +196,17 @@ impl IPCByteBuf<'_> {
         unsafe { addr.write_volatile(value); }
     }
 
+    pub fn rd16_volatile_ordered(&self, off: usize) -> u16 {
+        let value = self.rd16_volatile(off);
+        fence::io();
+        value
+    }
+
+    pub fn wr16_volatile_ordered(&mut self, off: usize, value: u16) {
+        fence::io();
+        self.wr16_volatile(off, value);
+    }
+
     #[cfg(target_has_atomic = "16")]
     pub fn wr16_atomic(&mut self, off: usize, value: u16) {
         unsafe {
@@ -146,6 +243,17 @@ impl IPCByteBuf<'_> {
         unsafe { addr.write_volatile(value); }
     }
 
+    pub fn rd32_volatile_ordered(&self, off: usize) -> u32 {
+        let value = self.rd32_volatile(off);
+        fence::io();
+        value
+    }
+
+    pub fn wr32_volatile_ordered(&mut self, off: usize, value: u32) {
+        fence::io();
+        self.wr32_volatile(off, value);
+    }
+
     #[cfg(target_has_atomic = "32")]
     pub fn wr32_atomic(&mut self, off: usize, value: u32) {
         unsafe {
@@ -182,6 +290,17 @@ impl IPCByteBuf<'_> {
         unsafe { addr.write_volatile(value); }
     }
 
+    pub fn rd64_volatile_ordered(&self, off: usize) -> u64 {
+        let value = self.rd64_volatile(off);
+        fence::io();
+        value
+    }
+
+    pub fn wr64_volatile_ordered(&mut self, off: usize, value: u64) {
+        fence::io();
+        self.wr64_volatile(off, value);
+    }
+
     #[cfg(target_has_atomic = "64")]
     pub fn wr64_atomic(&mut self, off: usize, value: u64) {
         unsafe {
@@ -190,3 +309,34 @@ impl IPCByteBuf<'_> {
         }
     }
 }
+
+/*
+ * sub-buffer chunks
+ */
+pub struct IPCByteBufChunks<'a> {
+    chunks: ChunksExactMut<'a, u8>,
+}
+
+impl<'a> IPCByteBufChunks<'a> {
+    pub fn into_remainder(self) -> IPCByteBuf<'a> {
+        IPCByteBuf {
+            buf: self.chunks.into_remainder(),
+        }
+    }
+}
+
+impl<'a> Iterator for IPCByteBufChunks<'a> {
+    type Item = IPCByteBuf<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(IPCByteBuf {
+            buf: self.chunks.next()?,
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.chunks.size_hint()
+    }
+}
+
+impl ExactSizeIterator for IPCByteBufChunks<'_> { }
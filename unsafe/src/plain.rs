@@ -0,0 +1,78 @@
+use core::mem::{ self };
+use core::slice::{ self };
+
+/// # Safety
+///
+/// Every bit pattern of `size_of::<Self>()` bytes must be a valid `Self`.
+pub unsafe trait FromBytes: Sized {
+    fn zeroed() -> Self {
+        unsafe { mem::zeroed() }
+    }
+
+    fn read_from(bytes: &[u8]) -> Option<Self> {
+        let bytes = bytes.get(..mem::size_of::<Self>())?;
+        Some(unsafe { bytes.as_ptr().cast::<Self>().read_unaligned() })
+    }
+}
+
+/// # Safety
+///
+/// `Self` must not contain padding or any other uninitialized bytes.
+pub unsafe trait AsBytes: Sized {
+    fn as_bytes(&self) -> &[u8] {
+        let addr = (self as *const Self).cast::<u8>();
+        unsafe { slice::from_raw_parts(addr, mem::size_of::<Self>()) }
+    }
+
+    fn as_bytes_mut(&mut self) -> &mut [u8]
+    where Self: FromBytes {
+        let addr = (self as *mut Self).cast::<u8>();
+        unsafe { slice::from_raw_parts_mut(addr, mem::size_of::<Self>()) }
+    }
+}
+
+macro_rules! plain_primitive {
+    ($($ty:ty),*) => {
+        $(
+            unsafe impl FromBytes for $ty { }
+            unsafe impl AsBytes for $ty { }
+        )*
+    };
+}
+
+plain_primitive!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+
+unsafe impl<T, const N: usize> FromBytes for [T; N] where T: FromBytes { }
+unsafe impl<T, const N: usize> AsBytes for [T; N] where T: AsBytes { }
+
+// expands to unsafe impls in the caller's crate on purpose, so overlays can
+// live next to their parsers in the forbid(unsafe_code) toolkit. the impls
+// are sound by construction: every field has to be FromBytes/AsBytes itself
+// and the size check below rejects padding, which is the whole contract
+#[macro_export]
+macro_rules! plain {
+    (
+        $(#[$meta:meta])*
+        $vis:vis struct $name:ident {
+            $($fvis:vis $field:ident: $ty:ty),* $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        #[repr(C)]
+        #[derive(Clone, Copy)]
+        $vis struct $name {
+            $($fvis $field: $ty),*
+        }
+
+        const _: () = assert!(
+            ::core::mem::size_of::<$name>() == 0 $(+ ::core::mem::size_of::<$ty>())*,
+            "plain struct must not contain padding",
+        );
+
+        unsafe impl $crate::plain::FromBytes for $name
+        where $($ty: $crate::plain::FromBytes),* { }
+
+        unsafe impl $crate::plain::AsBytes for $name
+        where $($ty: $crate::plain::AsBytes),* { }
+    };
+}
@@ -0,0 +1,119 @@
+use core::marker::{ PhantomData };
+use crate::{ IPCByteBuf };
+
+#[cfg(test)]
+mod test;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Error {
+    Unmapped,
+    Overlap,
+    Full,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct MemRegion {
+    pub addr: usize,
+    pub len: usize,
+}
+
+impl MemRegion {
+    pub fn new(addr: usize, len: usize) -> Self {
+        Self {
+            addr, len,
+        }
+    }
+
+    pub fn end(&self) -> Option<usize> {
+        self.addr.checked_add(self.len)
+    }
+
+    pub fn contains(&self, other: &Self) -> bool {
+        let (Some(end), Some(other_end)) = (self.end(), other.end()) else {
+            return false;
+        };
+        self.addr <= other.addr && other_end <= end
+    }
+
+    pub fn overlaps(&self, other: &Self) -> bool {
+        let (Some(end), Some(other_end)) = (self.end(), other.end()) else {
+            return true;
+        };
+        self.addr < other_end && other.addr < end
+    }
+}
+
+/*
+ * memory region registry
+ */
+pub struct IPCRegistry<'a, const MAPNR: usize, const NR: usize> {
+    map: [MemRegion; MAPNR],
+    taken: [Option<MemRegion>; NR],
+    mem: PhantomData<&'a mut [u8]>,
+}
+
+impl<'a, const NR: usize>
+From<IPCByteBuf<'a>> for IPCRegistry<'a, 1, NR> {
+    fn from(buf: IPCByteBuf<'a>) -> Self {
+        Self {
+            map: [MemRegion::new(buf.addr(), buf.len())],
+            taken: [None; NR],
+            mem: PhantomData,
+        }
+    }
+}
+
+impl<'a, const MAPNR: usize, const NR: usize>
+IPCRegistry<'a, MAPNR, NR> {
+    /// # Safety
+    ///
+    /// Every region of `map` must be valid memory for `'a` that is not
+    /// accessed through anything but this registry.
+    pub unsafe fn new(map: [MemRegion; MAPNR]) -> Self {
+        Self {
+            map,
+            taken: [None; NR],
+            mem: PhantomData,
+        }
+    }
+
+    pub fn map(&self) -> &[MemRegion] {
+        &self.map
+    }
+
+    pub fn taken(&self) -> impl Iterator<Item = &MemRegion> {
+        self.taken.iter().flatten()
+    }
+
+    pub fn is_mapped(&self, region: &MemRegion) -> bool {
+        self.map.iter().any(|map| map.contains(region))
+    }
+
+    pub fn is_taken(&self, region: &MemRegion) -> bool {
+        self.taken().any(|taken| taken.overlaps(region))
+    }
+
+    pub fn take(&mut self, addr: usize, len: usize) -> Result<IPCByteBuf<'a>, Error> {
+        let region = MemRegion::new(addr, len);
+        if !self.is_mapped(&region) {
+            return Err(Error::Unmapped);
+        }
+        if self.is_taken(&region) {
+            return Err(Error::Overlap);
+        }
+        let Some(slot) = self.taken.iter_mut().find(|slot| slot.is_none()) else {
+            return Err(Error::Full);
+        };
+        *slot = Some(region);
+        Ok(unsafe { IPCByteBuf::new(addr, len) })
+    }
+
+    pub fn give(&mut self, buf: IPCByteBuf<'a>) -> Result<(), IPCByteBuf<'a>> {
+        let region = MemRegion::new(buf.addr(), buf.len());
+        let Some(slot) = self.taken.iter_mut().find(|slot| **slot == Some(region)) else {
+            return Err(buf);
+        };
+        *slot = None;
+        Ok(())
+    }
+}
@@ -0,0 +1,43 @@
+use crate::{ IPCByteBuf };
+use crate::registry::{ Error, IPCRegistry, MemRegion };
+
+#[test]
+fn registry_take_give() {
+    let mut mem = [0u8; 64];
+    let mut reg: IPCRegistry<'_, 1, 2> = IPCRegistry::from(IPCByteBuf::from(&mut mem[..]));
+    let base = reg.map()[0].addr;
+
+    let first = reg.take(base, 16).unwrap();
+    assert_eq!((first.addr(), first.len()), (base, 16));
+    assert!(reg.is_taken(&MemRegion::new(base + 8, 1)));
+
+    // overlap is checked against every taken region, including partial ones
+    assert_eq!(reg.take(base, 16).err(), Some(Error::Overlap));
+    assert_eq!(reg.take(base + 15, 4).err(), Some(Error::Overlap));
+
+    let second = reg.take(base + 16, 16).unwrap();
+    assert_eq!(reg.take(base + 32, 16).err(), Some(Error::Full));
+
+    assert!(reg.give(first).is_ok());
+    assert_eq!(reg.taken().count(), 1);
+    let third = reg.take(base, 8).unwrap();
+
+    // only buffers handed out by take are accepted back
+    let mut other = [0u8; 4];
+    assert!(reg.give(IPCByteBuf::from(&mut other[..])).is_err());
+    assert!(reg.give(second).is_ok());
+    assert!(reg.give(third).is_ok());
+    assert_eq!(reg.taken().count(), 0);
+}
+
+#[test]
+fn registry_unmapped() {
+    let mut mem = [0u8; 64];
+    let mut reg: IPCRegistry<'_, 1, 2> = IPCRegistry::from(IPCByteBuf::from(&mut mem[..]));
+    let base = reg.map()[0].addr;
+
+    assert_eq!(reg.take(base + 60, 8).err(), Some(Error::Unmapped));
+    assert_eq!(reg.take(base.wrapping_sub(1), 2).err(), Some(Error::Unmapped));
+    assert_eq!(reg.take(base, usize::MAX).err(), Some(Error::Unmapped));
+    assert!(reg.take(base + 63, 1).is_ok());
+}
@@ -0,0 +1,92 @@
+use core::cell::{ UnsafeCell };
+use core::hint::{ self };
+use core::sync::atomic::{ AtomicUsize, Ordering };
+
+// lock word: free, held through with/try_with, or the owner passed to
+// with_owner plus one
+const FREE: usize = 0;
+const ANON: usize = usize::MAX;
+
+/*
+ * closure scoped spinlock. an interrupt handler spinning on a lock its own
+ * hart holds would never come back, with_owner turns that into None
+ */
+#[derive(Default)]
+pub struct SpinLock<T> {
+    lock: AtomicUsize,
+    inner: UnsafeCell<T>,
+}
+
+unsafe impl<T: Send> Sync for SpinLock<T> { }
+
+// released on the way out even if f unwinds
+struct Unlock<'a>(&'a AtomicUsize);
+
+impl Drop for Unlock<'_> {
+    fn drop(&mut self) {
+        self.0.store(FREE, Ordering::Release);
+    }
+}
+
+impl<T> SpinLock<T> {
+    pub const fn new(inner: T) -> Self {
+        Self {
+            lock: AtomicUsize::new(FREE),
+            inner: UnsafeCell::new(inner),
+        }
+    }
+
+    fn acquire(&self, tag: usize) -> Result<usize, usize> {
+        self.lock.compare_exchange_weak(FREE, tag, Ordering::Acquire, Ordering::Relaxed)
+    }
+
+    fn locked<R, F: FnOnce(&mut T) -> R>(&self, f: F) -> R {
+        let _unlock = Unlock(&self.lock);
+        f(unsafe { &mut *self.inner.get() })
+    }
+
+    pub fn with<R, F: FnOnce(&mut T) -> R>(&self, f: F) -> R {
+        while self.acquire(ANON).is_err() {
+            hint::spin_loop();
+        }
+        self.locked(f)
+    }
+
+    // strong exchange, a weak one could report contention on a free lock
+    pub fn try_with<R, F: FnOnce(&mut T) -> R>(&self, f: F) -> Option<R> {
+        self.lock.compare_exchange(FREE, ANON, Ordering::Acquire, Ordering::Relaxed).ok()?;
+        Some(self.locked(f))
+    }
+
+    fn tag(owner: usize) -> Option<usize> {
+        owner.checked_add(1).filter(|tag| *tag != ANON)
+    }
+
+    // spins while another owner holds the lock, None if owner already does.
+    // only owner ever stores its own tag, so seeing it means re-entry
+    pub fn with_owner<R, F: FnOnce(&mut T) -> R>(&self, owner: usize, f: F) -> Option<R> {
+        let tag = Self::tag(owner)?;
+        loop {
+            match self.acquire(tag) {
+                Ok(_) => return Some(self.locked(f)),
+                Err(held) if held == tag => return None,
+                Err(_) => hint::spin_loop(),
+            }
+        }
+    }
+
+    // one attempt, None while anyone holds the lock
+    pub fn try_with_owner<R, F: FnOnce(&mut T) -> R>(&self, owner: usize, f: F) -> Option<R> {
+        let tag = Self::tag(owner)?;
+        self.lock.compare_exchange(FREE, tag, Ordering::Acquire, Ordering::Relaxed).ok()?;
+        Some(self.locked(f))
+    }
+
+    pub fn get_mut(&mut self) -> &mut T {
+        self.inner.get_mut()
+    }
+
+    pub fn into_inner(self) -> T {
+        self.inner.into_inner()
+    }
+}
@@ -0,0 +1,113 @@
+extern crate std;
+
+use crate::{ IPCByteBuf, fence };
+use crate::spin::{ SpinLock };
+
+#[test]
+fn ipcbuf_slice() {
+    let mut mem: [u8; 8] = core::array::from_fn(|i| i as u8);
+    let mut buf = IPCByteBuf::from(&mut mem[..]);
+
+    let mut sub = buf.slice(2..6).unwrap();
+    assert_eq!(sub.len(), 4);
+    assert_eq!(sub.rd8(0), 2);
+    sub.wr8(3, 0xff);
+    assert_eq!(buf.rd8(5), 0xff);
+
+    assert_eq!(buf.slice(..).unwrap().len(), 8);
+    assert_eq!(buf.slice(8..).unwrap().len(), 0);
+    assert!(buf.slice(4..9).is_none());
+    assert!(buf.slice(9..).is_none());
+    let (start, end) = (6, 2);
+    assert!(buf.slice(start..end).is_none());
+}
+
+#[test]
+fn ipcbuf_split_at() {
+    let mut mem: [u8; 8] = core::array::from_fn(|i| i as u8);
+    let mut buf = IPCByteBuf::from(&mut mem[..]);
+
+    let (left, right) = buf.split_at(3).unwrap();
+    assert_eq!((left.len(), right.len()), (3, 5));
+    assert_eq!(right.rd8(0), 3);
+
+    let (left, right) = buf.split_at(8).unwrap();
+    assert_eq!((left.len(), right.len()), (8, 0));
+    assert!(buf.split_at(9).is_none());
+}
+
+#[test]
+fn ipcbuf_chunks() {
+    let mut mem: [u8; 10] = core::array::from_fn(|i| i as u8);
+    let mut buf = IPCByteBuf::from(&mut mem[..]);
+
+    let mut chunks = buf.split_into_chunks(3);
+    assert_eq!(chunks.len(), 3);
+    for idx in 0..3 {
+        let chunk = chunks.next().unwrap();
+        assert_eq!(chunk.len(), 3);
+        assert_eq!(chunk.rd8(0), idx * 3);
+    }
+    assert!(chunks.next().is_none());
+    let rest = chunks.into_remainder();
+    assert_eq!(rest.len(), 1);
+    assert_eq!(rest.rd8(0), 9);
+
+    // more chunks than bytes leaves everything in the remainder
+    let mut chunks = buf.split_into_chunks(11);
+    assert!(chunks.next().is_none());
+    assert_eq!(chunks.into_remainder().len(), 10);
+
+    let mut chunks = buf.split_into_chunks(0);
+    assert!(chunks.next().is_none());
+    assert_eq!(chunks.into_remainder().len(), 10);
+}
+
+#[test]
+fn ipcbuf_ordered() {
+    let mut mem = [0u8; 16];
+    let mut buf = IPCByteBuf::from(&mut mem[..]);
+
+    buf.wr8_volatile_ordered(0, 0x11);
+    buf.wr16_volatile_ordered(2, 0x2233);
+    buf.wr32_volatile_ordered(4, 0x4455_6677);
+    buf.wr64_volatile_ordered(8, 0x8899_aabb_ccdd_eeff);
+    fence::io();
+
+    assert_eq!(buf.rd8_volatile_ordered(0), 0x11);
+    assert_eq!(buf.rd16_volatile_ordered(2), 0x2233);
+    assert_eq!(buf.rd32_volatile_ordered(4), 0x4455_6677);
+    assert_eq!(buf.rd64_volatile_ordered(8), 0x8899_aabb_ccdd_eeff);
+
+    // ordered and plain accessors see the same memory
+    assert_eq!(buf.rd32(4), 0x4455_6677);
+    buf.wr16(2, 0xbeef);
+    assert_eq!(buf.rd16_volatile_ordered(2), 0xbeef);
+    assert_eq!(mem[8..], 0x8899_aabb_ccdd_eeffu64.to_ne_bytes());
+}
+
+#[test]
+fn spin_owner() {
+    let lock = SpinLock::new(0u32);
+
+    assert_eq!(lock.with_owner(1, |outer| {
+        *outer += 1;
+        // the same owner would spin on itself forever, another one waits
+        assert_eq!(lock.with_owner(1, |inner| *inner), None);
+        assert_eq!(lock.try_with(|inner| *inner), None);
+        assert_eq!(lock.try_with_owner(1, |inner| *inner), None);
+        assert_eq!(lock.try_with_owner(2, |inner| *inner), None);
+        *outer
+    }), Some(1));
+    assert_eq!(lock.with_owner(2, |value| *value), Some(1));
+    assert_eq!(lock.try_with_owner(2, |value| *value), Some(1));
+    assert_eq!(lock.with_owner(usize::MAX, |value| *value), None);
+    assert_eq!(lock.try_with_owner(usize::MAX, |value| *value), None);
+
+    // a panicking closure still lets go of the lock
+    let res = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        lock.with(|_| panic!("poisoned"));
+    }));
+    assert!(res.is_err());
+    assert_eq!(lock.try_with(|value| *value), Some(1));
+}
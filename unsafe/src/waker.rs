@@ -0,0 +1,22 @@
+use core::sync::atomic::{ AtomicBool, Ordering };
+use core::task::{ RawWaker, RawWakerVTable, Waker };
+
+// wakers only ever point at a 'static flag, so clones may outlive whoever
+// created them and dropping one has nothing to release
+static VTABLE: RawWakerVTable = RawWakerVTable::new(clone, wake, wake, drop);
+
+fn clone(data: *const ()) -> RawWaker {
+    RawWaker::new(data, &VTABLE)
+}
+
+fn wake(data: *const ()) {
+    let flag = unsafe { &*data.cast::<AtomicBool>() };
+    flag.store(true, Ordering::Release);
+}
+
+fn drop(_: *const ()) { }
+
+pub fn flag_waker(flag: &'static AtomicBool) -> Waker {
+    let data = (flag as *const AtomicBool).cast::<()>();
+    unsafe { Waker::from_raw(RawWaker::new(data, &VTABLE)) }
+}